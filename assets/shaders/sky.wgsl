@@ -0,0 +1,46 @@
+#import bevy_pbr::forward_io::VertexOutput
+
+struct SkyMaterial {
+    zenith: vec4<f32>,
+    horizon: vec4<f32>,
+    // xy position, z radius, w phase (0 new, 0.5 full)
+    moon: vec4<f32>,
+    // rgb color, a visibility
+    moon_color: vec4<f32>,
+    // x lightning flash, y horizon height, z zenith height
+    params: vec4<f32>,
+};
+
+@group(2) @binding(100) var<uniform> material: SkyMaterial;
+
+fn disc(p: vec2<f32>, center: vec2<f32>, r: f32) -> f32 {
+    let d = length(p - center);
+    return 1.0 - smoothstep(r - 0.05, r + 0.05, d);
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let p = in.world_position.xy;
+    let t = clamp((p.y - material.params.y) / (material.params.z - material.params.y), 0.0, 1.0);
+    var color = mix(material.horizon.rgb, material.zenith.rgb, smoothstep(0.0, 1.0, t));
+
+    // The shadow is a second disc sliding across the moon, off to the side when full
+    let r = material.moon.z;
+    let phase = material.moon.w;
+    let fullness = 1.0 - abs(1.0 - 2.0 * phase);
+    let side = select(1.0, -1.0, phase < 0.5);
+    let shadow_center = material.moon.xy + vec2(side * 2.0 * r * fullness, 0.0);
+    let moon = disc(p, material.moon.xy, r);
+    let lit = moon * (1.0 - disc(p, shadow_center, r));
+    let halo = exp(-max(length(p - material.moon.xy) - r, 0.0) / (r * 0.8)) * 0.3 * fullness;
+
+    let visibility = material.moon_color.a;
+    color = mix(color, color * 0.7, moon * visibility);
+    color = mix(color, material.moon_color.rgb, lit * visibility);
+    color += material.moon_color.rgb * halo * visibility;
+
+    color += vec3(0.8, 0.8, 1.0) * material.params.x;
+    return vec4(color, 1.0);
+}
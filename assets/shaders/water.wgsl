@@ -0,0 +1,66 @@
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+    mesh_view_bindings::globals,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+const MAX_IMPACTS: u32 = 8u;
+const LIFETIME: f32 = 2.5;
+const RIPPLE_SPEED: f32 = 4.0;
+
+struct WaterExtension {
+    // xy position, z time of impact, w strength
+    impacts: array<vec4<f32>, 8>,
+};
+
+@group(2) @binding(100) var<uniform> water: WaterExtension;
+
+// Rings spreading out from every recent impact, squashed since the surface is seen side on
+fn ripples(p: vec2<f32>) -> f32 {
+    var total = 0.0;
+    for (var i = 0u; i < MAX_IMPACTS; i++) {
+        let impact = water.impacts[i];
+        let age = globals.time - impact.z;
+        if impact.w <= 0.0 || age < 0.0 || age > LIFETIME {
+            continue;
+        }
+        let d = length((p - impact.xy) * vec2(1.0, 2.5));
+        let r = age * RIPPLE_SPEED;
+        let fade = (1.0 - age / LIFETIME) * (1.0 - age / LIFETIME);
+        let ring = exp(-pow((d - r) * 2.0, 2.0));
+        let wake = select(0.0, 0.5 + 0.5 * sin(d * 6.0 - age * 12.0), d < r) * exp(-max(r - d, 0.0));
+        total += (ring + wake * 0.4) * fade * impact.w;
+    }
+    return total;
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+    pbr_input.material.emissive += vec4(vec3(0.5, 0.55, 0.65) * ripples(in.world_position.xy), 0.0);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+    return out;
+}
@@ -3,6 +3,7 @@ fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=art/blocks.blend");
     println!("cargo:rerun-if-changed=assets/audio");
+    println!("cargo:rerun-if-changed=assets/foundations");
 
     let cargo_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap();
     let out_dir = std::env::var_os("OUT_DIR").unwrap();
@@ -68,6 +69,26 @@ fn main() {
         cloud_count
     ));
 
+    let mut level_paths = String::new();
+    let mut level_count = 0;
+    let mut level_entries: Vec<_> =
+        std::fs::read_dir(std::path::Path::new(&cargo_dir).join("assets/foundations"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+    level_entries.sort();
+    for path in level_entries {
+        let filename = path.file_name().unwrap().to_str().unwrap();
+        if filename.ends_with(".level.json") {
+            level_paths.push_str(&format!("\"foundations/{}\",", filename));
+            level_count += 1;
+        }
+    }
+    code.push_str(&format!(
+        "const LEVELS: [&'static str; {}] = [{level_paths}];",
+        level_count
+    ));
+
     for (name, dir) in [
         ("CLANKS", "audio/clank"),
         ("SQUELCHES", "audio/squelch"),
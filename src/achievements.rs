@@ -0,0 +1,397 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::{AnchorColor, AnchorState, Anchors, Block},
+    spawn_corner_buttons, storage, CornerButtons, CornerPanel,
+};
+
+#[derive(Event)]
+pub struct BlockPlaced(pub Entity);
+
+/// A lightning strike has been released, `decayed` blocks were ruined by it directly.
+#[derive(Event)]
+pub struct DisasterResolved {
+    pub decayed: usize,
+}
+
+/// How many conductors a lightning strike arced through, and how many of those were
+/// `WeirdMachine`s.
+#[derive(Event)]
+pub struct LightningChain {
+    pub length: usize,
+    pub machines: usize,
+}
+
+#[derive(Event)]
+pub struct RoundScored {
+    pub score: i32,
+    pub intact: usize,
+    pub decayed: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstStone,
+    Cataclysm,
+    Spire,
+    MadScience,
+    ChainLightning,
+    TotalRuin,
+    Connoisseur,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 7] = [
+        Achievement::FirstStone,
+        Achievement::Cataclysm,
+        Achievement::Spire,
+        Achievement::MadScience,
+        Achievement::ChainLightning,
+        Achievement::TotalRuin,
+        Achievement::Connoisseur,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Achievement::FirstStone => "First Stone",
+            Achievement::Cataclysm => "Cataclysm",
+            Achievement::Spire => "Spire of Dread",
+            Achievement::MadScience => "Mad Science",
+            Achievement::ChainLightning => "Chain Lightning",
+            Achievement::TotalRuin => "Total Ruin",
+            Achievement::Connoisseur => "Connoisseur of Decay",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstStone => "Place a block",
+            Achievement::Cataclysm => "Decay 30 blocks in one strike",
+            Achievement::Spire => "Build 15 blocks high",
+            Achievement::MadScience => "Power three Weird Machines in one chain",
+            Achievement::ChainLightning => "Arc through 10 conductors in one strike",
+            Achievement::TotalRuin => "Finish with zero intact blocks",
+            Achievement::Connoisseur => "Score 100 points in one round",
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct Unlocked(pub Vec<Achievement>);
+
+#[derive(Component)]
+struct Toast(Timer);
+
+#[derive(Component)]
+struct AchievementsButton;
+
+#[derive(Component)]
+struct AchievementsMenu;
+
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+pub struct AchievementPlugin;
+
+impl Plugin for AchievementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BlockPlaced>()
+            .add_event::<DisasterResolved>()
+            .add_event::<LightningChain>()
+            .add_event::<RoundScored>()
+            .add_systems(Startup, (load, spawn_button.after(spawn_corner_buttons)))
+            .add_systems(
+                Update,
+                (
+                    (
+                        block_placed,
+                        disaster_resolved,
+                        lightning_chain,
+                        round_scored,
+                    ),
+                    update_toasts,
+                    toggle_menu,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn load(mut commands: Commands) {
+    commands.insert_resource(storage::load_json::<Unlocked>("achievements").unwrap_or_default());
+}
+
+fn unlock(
+    commands: &mut Commands,
+    unlocked: &mut Unlocked,
+    toasts: &Query<Entity, With<Toast>>,
+    achievement: Achievement,
+) {
+    if unlocked.0.contains(&achievement) {
+        return;
+    }
+    unlocked.0.push(achievement);
+    storage::save_json("achievements", unlocked);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(24.0),
+                    top: Val::Px(24.0 + 72.0 * toasts.iter().count() as f32),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(Color::srgb(1.0, 0.85, 0.4)),
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            Toast(Timer::new(TOAST_DURATION, TimerMode::Once)),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Achievement: {}", achievement.title()),
+                TextStyle {
+                    font_size: 22.0,
+                    color: Color::srgb(1.0, 0.85, 0.4),
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                achievement.description(),
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::srgb(0.8, 0.8, 0.8),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Number of blocks stacked under `entity`, counting itself, following occupied `Down` anchors.
+fn stack_height(entity: Entity, anchors: &Query<&Anchors, With<Block>>, depth: usize) -> usize {
+    // Guards against cycles in malformed anchor graphs
+    if depth > 64 {
+        return depth;
+    }
+    let Ok(block_anchors) = anchors.get(entity) else {
+        return 0;
+    };
+    1 + block_anchors
+        .0
+        .iter()
+        .filter_map(|(_, color, state, _)| match (color, state) {
+            (AnchorColor::Down, AnchorState::Occupied(below)) => Some(*below),
+            _ => None,
+        })
+        .map(|below| stack_height(below, anchors, depth + 1))
+        .max()
+        .unwrap_or(0)
+}
+
+fn block_placed(
+    mut commands: Commands,
+    mut events: EventReader<BlockPlaced>,
+    mut unlocked: ResMut<Unlocked>,
+    toasts: Query<Entity, With<Toast>>,
+    anchors: Query<&Anchors, With<Block>>,
+) {
+    for BlockPlaced(entity) in events.read() {
+        unlock(
+            &mut commands,
+            &mut unlocked,
+            &toasts,
+            Achievement::FirstStone,
+        );
+        if stack_height(*entity, &anchors, 0) >= 15 {
+            unlock(&mut commands, &mut unlocked, &toasts, Achievement::Spire);
+        }
+    }
+}
+
+fn disaster_resolved(
+    mut commands: Commands,
+    mut events: EventReader<DisasterResolved>,
+    mut unlocked: ResMut<Unlocked>,
+    toasts: Query<Entity, With<Toast>>,
+) {
+    for event in events.read() {
+        if event.decayed >= 30 {
+            unlock(
+                &mut commands,
+                &mut unlocked,
+                &toasts,
+                Achievement::Cataclysm,
+            );
+        }
+    }
+}
+
+fn lightning_chain(
+    mut commands: Commands,
+    mut events: EventReader<LightningChain>,
+    mut unlocked: ResMut<Unlocked>,
+    toasts: Query<Entity, With<Toast>>,
+) {
+    for event in events.read() {
+        if event.machines >= 3 {
+            unlock(
+                &mut commands,
+                &mut unlocked,
+                &toasts,
+                Achievement::MadScience,
+            );
+        }
+        if event.length >= 10 {
+            unlock(
+                &mut commands,
+                &mut unlocked,
+                &toasts,
+                Achievement::ChainLightning,
+            );
+        }
+    }
+}
+
+fn round_scored(
+    mut commands: Commands,
+    mut events: EventReader<RoundScored>,
+    mut unlocked: ResMut<Unlocked>,
+    toasts: Query<Entity, With<Toast>>,
+) {
+    for event in events.read() {
+        if event.intact == 0 && event.decayed > 0 {
+            unlock(
+                &mut commands,
+                &mut unlocked,
+                &toasts,
+                Achievement::TotalRuin,
+            );
+        }
+        if event.score >= 100 {
+            unlock(
+                &mut commands,
+                &mut unlocked,
+                &toasts,
+                Achievement::Connoisseur,
+            );
+        }
+    }
+}
+
+fn update_toasts(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Toast, &mut Style, &mut BackgroundColor)>,
+    time: Res<Time>,
+) {
+    for (entity, mut toast, mut style, mut color) in &mut query {
+        toast.0.tick(time.delta());
+        if toast.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // Slide in, then fade out over the last second
+        let t = toast.0.elapsed_secs();
+        style.left = Val::Px(24.0 - 300.0 * (1.0 - (t * 4.0).min(1.0)).powi(2));
+        color.0.set_alpha(0.8 * toast.0.remaining_secs().min(1.0));
+    }
+}
+
+fn spawn_button(mut commands: Commands, corner: Query<Entity, With<CornerButtons>>) {
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                    ..default()
+                },
+                border_radius: BorderRadius::MAX,
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            AchievementsButton,
+        ))
+        .set_parent(corner.single())
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Achievements",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::srgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn toggle_menu(
+    mut commands: Commands,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<AchievementsButton>)>,
+    menu: Query<Entity, With<AchievementsMenu>>,
+    panels: Query<Entity, With<CornerPanel>>,
+    unlocked: Res<Unlocked>,
+) {
+    if !interaction_query.iter().any(|i| *i == Interaction::Pressed) {
+        return;
+    }
+    if let Ok(menu) = menu.get_single() {
+        commands.entity(menu).despawn_recursive();
+        return;
+    }
+    for panel in &panels {
+        commands.entity(panel).despawn_recursive();
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(16.0),
+                    bottom: Val::Px(56.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            AchievementsMenu,
+            CornerPanel,
+        ))
+        .with_children(|parent| {
+            for achievement in Achievement::ALL {
+                let color = if unlocked.0.contains(&achievement) {
+                    Color::srgb(1.0, 0.85, 0.4)
+                } else {
+                    Color::srgb(0.4, 0.4, 0.4)
+                };
+                parent.spawn(TextBundle::from_sections([
+                    TextSection::new(
+                        format!("{}\n", achievement.title()),
+                        TextStyle {
+                            font_size: 20.0,
+                            color,
+                            ..default()
+                        },
+                    ),
+                    TextSection::new(
+                        achievement.description(),
+                        TextStyle {
+                            font_size: 15.0,
+                            color: Color::srgb(0.7, 0.7, 0.7),
+                            ..default()
+                        },
+                    ),
+                ]));
+            }
+        });
+}
@@ -22,6 +22,37 @@ pub enum DisasterTarget {
 #[reflect(Component)]
 pub struct Conductor;
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum BlockTag {
+    Tower,
+    Hall,
+    Chapel,
+    Crypt,
+    Belfry,
+    Machine,
+}
+
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct BlockTags(pub Vec<BlockTag>);
+
+/// Per block type scoring, authored alongside the block. Blocks without it use the defaults.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct BlockScoring {
+    pub spookiness: f32,
+    pub cheer: f32,
+}
+
+impl Default for BlockScoring {
+    fn default() -> Self {
+        Self {
+            spookiness: 1.0,
+            cheer: 0.5,
+        }
+    }
+}
+
 #[derive(Component, Reflect, Copy, Clone)]
 #[reflect(Component)]
 pub struct NoCollide;
@@ -74,8 +105,18 @@ pub struct MouseAnchor;
 #[reflect(Component)]
 pub struct DecayedRepresentation(pub String);
 
+impl DecayedRepresentation {
+    pub fn path(&self) -> String {
+        if self.0.ends_with(".glb") {
+            self.0.clone()
+        } else {
+            format!("levels/{}.glb", self.0)
+        }
+    }
+}
+
 #[derive(Component)]
-struct Collider(Box<dyn parry3d::shape::Shape>);
+pub struct Collider(pub Box<dyn parry3d::shape::Shape>);
 
 #[derive(Component)]
 pub struct InCollision;
@@ -95,6 +136,9 @@ impl Plugin for BlockPlugin {
             .register_type::<WeirdMachine>()
             .register_type::<AnchorColor>()
             .register_type::<DecayedRepresentation>()
+            .register_type::<BlockTag>()
+            .register_type::<BlockTags>()
+            .register_type::<BlockScoring>()
             .register_type::<DisasterTarget>()
             .register_type::<MouseAnchor>()
             .register_type::<Pickable>()
@@ -110,6 +154,8 @@ impl Plugin for BlockPlugin {
                     configure_anchors,
                     befuddle_weird_machines,
                     lift_component::<DecayedRepresentation>,
+                    lift_component::<BlockTags>,
+                    lift_component::<BlockScoring>,
                     lift_component::<NoCollide>,
                 ),
             )
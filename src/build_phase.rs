@@ -1,17 +1,19 @@
 use bevy::{core_pipeline::tonemapping::Tonemapping, prelude::*};
 use bevy_kira_audio::prelude::*;
 use bevy_mod_picking::prelude::*;
-use blenvy::{
-    BlueprintAnimationPlayerLink, BlueprintAnimations, BlueprintInfo, GameWorldTag, HideUntilReady,
-    SpawnBlueprint,
-};
+use blenvy::{BlueprintAnimationPlayerLink, BlueprintAnimations};
 
 use crate::{
+    achievements::BlockPlaced,
     block::{AnchorState, Block},
     block_pool::BlockPoolResident,
-    environmental_decoration::{Sky, Water, TimeOfDay},
-    crow::{CrowPickupTarget, CrowTakeawayTarget, Grab, Crow},
-    CameraScale, GameState, Lift, MousePos, SavedPosition, Spawned, SpawnedFrom, Spawner, BLOCKS,
+    environmental_decoration::{Water, TimeOfDay},
+    crow::{CrowTakeawayTarget, Grab, Crow},
+    crow_jobs::{CrowJob, CrowJobKind},
+    interpolation::Interpolated,
+    music::EffectsChannel,
+    levels::SpareTentacle,
+    CameraScale, GameState, Lift, MousePos, RoundRng, SavedPosition, Spawned, SpawnedFrom, Spawner, WorldClicks, BLOCKS,
     SNAP_DISTANCE,
 };
 
@@ -28,13 +30,13 @@ pub struct AwaitingPlacement;
 #[derive(Copy, Clone, Component)]
 pub struct OnTentacle;
 
-#[derive(Debug, Component)]
+#[derive(Debug, Component, Reflect, Clone, PartialEq)]
 pub struct Snapped {
-    a_entity: Entity,
-    a_anchor: usize,
-    b_entity: Entity,
-    b_anchor: usize,
-    a_translation: Vec3,
+    pub a_entity: Entity,
+    pub a_anchor: usize,
+    pub b_entity: Entity,
+    pub b_anchor: usize,
+    pub a_translation: Vec3,
 }
 
 #[derive(Component, Reflect)]
@@ -130,17 +132,10 @@ impl Plugin for BuildPhasePlugin {
 
 fn setup_phase(
     mut commands: Commands,
-    query: Query<(Entity, &TentacleSpawner)>,
+    query: Query<(Entity, &TentacleSpawner), Without<SpareTentacle>>,
     crows: Query<Entity, With<Crow>>,
     spawn_points: Query<Entity, With<Spawner>>,
-    mut sky: Query<&mut Sky>,
-    time: Res<Time>,
-    mut time_of_day: ResMut<TimeOfDay>,
 ) {
-    *time_of_day = TimeOfDay::Day;
-    for mut sky in &mut sky {
-        sky.to_day(time.elapsed());
-    }
     for (entity, spawner) in &query {
         commands.entity(entity).remove::<Dead>().insert(Visibility::Visible).insert(Retracting);
         if let Ok(e) = spawn_points.get(spawner.0) {
@@ -166,13 +161,6 @@ fn setup(
         .into(),
         ..default()
     },));
-    commands.spawn((
-        BlueprintInfo::from_path("levels/_foundation.glb"),
-        Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
-        SpawnBlueprint,
-        HideUntilReady,
-        GameWorldTag,
-    ));
 }
 
 fn start_retract(
@@ -192,13 +180,14 @@ fn spawn_block(
     tentacles: Query<&TentacleSpawner, With<Extending>>,
     spawn_points: Query<Entity, (With<Spawner>, Without<Spawned>)>,
     block_pool: Query<(Entity, &BlockPoolResident)>,
-    audio: Res<Audio>,
+    effects: Res<AudioChannel<EffectsChannel>>,
     splashes: Res<crate::music::Splashes>,
+    mut rng: ResMut<RoundRng>,
     mut count: Local<usize>,
 ) {
     for tentacle_spawner in &tentacles {
         if let Ok(spawner_entity) = spawn_points.get(tentacle_spawner.0) {
-            let draw = fastrand::f32();
+            let draw = rng.0.f32();
             let idx = BLOCKS
                 .iter()
                 .position(|(_, p)| *p >= draw)
@@ -213,7 +202,7 @@ fn spawn_block(
             }
             if let Some(entity) = found {
                 if *count >= 4 {
-                    audio
+                    effects
                         .play(splashes.0[fastrand::usize(0..splashes.0.len())].clone())
                         .with_volume(0.25);
                 }
@@ -240,10 +229,10 @@ fn spawn_block(
 fn play_squelch(
     squelches: Res<crate::music::Squelches>,
     query: Query<Entity, Added<AwaitingPlacement>>,
-    audio: Res<Audio>,
+    effects: Res<AudioChannel<EffectsChannel>>,
 ) {
     for _ in &query {
-        audio.play(squelches.0[fastrand::usize(0..squelches.0.len())].clone());
+        effects.play(squelches.0[fastrand::usize(0..squelches.0.len())].clone());
     }
 }
 
@@ -254,7 +243,7 @@ fn stop_drag(
             Entity,
             &SpawnedFrom,
             &mut Transform,
-            Option<&mut Snapped>,
+            Option<&Snapped>,
             Option<&SavedPosition>,
         ),
         (
@@ -266,37 +255,25 @@ fn stop_drag(
     children_query: Query<&Children>,
     water: Query<&GlobalTransform, With<Water>>,
     mut anchors: Query<&mut crate::block::Anchors>,
+    mut placed: EventWriter<BlockPlaced>,
 ) {
     for (entity, spawned_from, mut transform, maybe_snapped, maybe_saved) in &mut query {
         commands
             .entity(entity)
             .remove::<Snapped>()
-            .remove::<CrowPickupTarget>()
+            .remove::<CrowJob>()
             .remove::<SavedPosition>();
-        if let Some(Snapped {
-            a_entity,
-            a_anchor,
-            b_entity,
-            b_anchor,
-            a_translation,
-        }) = maybe_snapped.map(|v| v.into_inner())
-        {
-            if let Ok(mut anchors) = anchors.get_mut(*a_entity) {
-                anchors.0[*a_anchor].2 = AnchorState::Occupied(*b_entity);
-            }
-            if let Ok(mut anchors) = anchors.get_mut(*b_entity) {
-                anchors.0[*b_anchor].2 = AnchorState::Occupied(*a_entity);
-            }
-            commands.entity(spawned_from.0).remove::<Spawned>();
-            commands
-                .entity(entity)
-                .remove::<OnTentacle>()
-                .insert(Pickable::IGNORE)
-                .insert(NeedsClearance);
-            for descendant in children_query.iter_descendants(entity) {
-                commands.entity(descendant).insert(Pickable::IGNORE);
-            }
-            transform.translation = *a_translation;
+        if let Some(snapped) = maybe_snapped {
+            place_block(
+                &mut commands,
+                entity,
+                spawned_from.0,
+                &mut transform,
+                snapped,
+                &mut anchors,
+                &children_query,
+                &mut placed,
+            );
         } else {
             let mut found = None;
             for (e, grab) in &crows {
@@ -304,7 +281,7 @@ fn stop_drag(
                     found= Some(e);
                     commands
                         .entity(entity)
-                        .insert(CrowTakeawayTarget);
+                        .insert((CrowTakeawayTarget, Interpolated::default()));
                 }
             }
             if found.is_none() {
@@ -316,11 +293,50 @@ fn stop_drag(
     }
 }
 
+/// Settles `entity` into the anchors `snapped` found for it and releases it from the spawner
+/// or crow it hung from.
+pub fn place_block(
+    commands: &mut Commands,
+    entity: Entity,
+    spawned_from: Entity,
+    transform: &mut Transform,
+    snapped: &Snapped,
+    anchors: &mut Query<&mut crate::block::Anchors>,
+    children_query: &Query<&Children>,
+    placed: &mut EventWriter<BlockPlaced>,
+) {
+    if let Ok(mut anchors) = anchors.get_mut(snapped.a_entity) {
+        anchors.0[snapped.a_anchor].2 = AnchorState::Occupied(snapped.b_entity);
+    }
+    if let Ok(mut anchors) = anchors.get_mut(snapped.b_entity) {
+        anchors.0[snapped.b_anchor].2 = AnchorState::Occupied(snapped.a_entity);
+    }
+    commands.entity(spawned_from).remove::<Spawned>();
+    commands
+        .entity(entity)
+        .remove::<OnTentacle>()
+        .insert(Pickable::IGNORE)
+        .insert(NeedsClearance);
+    for descendant in children_query.iter_descendants(entity) {
+        commands.entity(descendant).insert(Pickable::IGNORE);
+    }
+    transform.translation = snapped.a_translation;
+    placed.send(BlockPlaced(entity));
+}
+
+/// Picks up the dragged block, unless the drag began on a button or slider over it.
 fn start_drag(
     mut commands: Commands,
     mut query: Query<(Entity, &mut Transform), (With<AwaitingPlacement>, Without<SavedPosition>)>,
     foundation: Query<&GlobalTransform, With<Foundation>>,
+    clicks: WorldClicks,
 ) {
+    if clicks.over_ui() {
+        for (entity, _) in &query {
+            commands.entity(entity).remove::<AwaitingPlacement>();
+        }
+        return;
+    }
     if let Some(foundation) = foundation.iter().next() {
         let foundation_z = foundation.translation().z;
         for (entity, mut transform) in &mut query {
@@ -351,7 +367,7 @@ fn follow_mouse(
     >,
     camera_scale: Res<CameraScale>,
     clanks: Res<crate::music::Clanks>,
-    audio: Res<Audio>,
+    effects: Res<AudioChannel<EffectsChannel>>,
 ) {
     for (entity, mut transform, anchors, in_collision) in &mut query {
         let mut snapped = None;
@@ -407,9 +423,9 @@ fn follow_mouse(
         transform.translation.y = maybe_pos.y;
 
         if transform.translation.y > 10.0 {
-            commands.entity(entity).insert(CrowPickupTarget);
+            commands.entity(entity).insert(CrowJob::new(CrowJobKind::Pickup));
         } else {
-            commands.entity(entity).remove::<CrowPickupTarget>();
+            commands.entity(entity).remove::<CrowJob>();
         }
 
         if let Some(snapped) = snapped {
@@ -418,10 +434,10 @@ fn follow_mouse(
                     || snapped.a_anchor != prev_snapped.a_anchor
                     || snapped.b_anchor != prev_snapped.b_anchor
                 {
-                    audio.play(clanks.0[fastrand::usize(0..clanks.0.len())].clone());
+                    effects.play(clanks.0[fastrand::usize(0..clanks.0.len())].clone());
                 }
             } else {
-                audio.play(clanks.0[fastrand::usize(0..clanks.0.len())].clone());
+                effects.play(clanks.0[fastrand::usize(0..clanks.0.len())].clone());
             }
             commands.entity(entity).insert(snapped);
         } else {
@@ -547,7 +563,6 @@ fn check_completion(
             Without<BlockPoolResident>,
         ),
     >,
-    mut sky: Query<&mut Sky>,
     time: Res<Time>,
     mut next_state: ResMut<NextState<PhasePhase>>,
     mut delay: Local<bevy::time::Stopwatch>,
@@ -578,12 +593,9 @@ fn check_completion(
 
     if any_non_foundation && done.unwrap_or(false) {
         if delay.elapsed() > std::time::Duration::from_millis(250) {
-            for mut sky in &mut sky {
-                sky.to_night(time.elapsed());
-            }
             next_state.set(PhasePhase::ShuttingDown);
+            *time_of_day = TimeOfDay::Night;
         }
-        *time_of_day = TimeOfDay::Night;
     } else {
         delay.reset();
     }
@@ -605,14 +617,10 @@ fn retract_tentacles(
 fn hide_tentacles(
     mut commands: Commands,
     query: Query<Entity, With<Tentacle>>,
-    crows: Query<Entity, With<Crow>>,
 ) {
     for entity in &query {
         commands.entity(entity).insert(Visibility::Hidden);
     }
-    for entity in &crows {
-        commands.entity(entity).insert(Visibility::Hidden);
-    }
 }
 
 fn despawn_spare_blocks(mut commands: Commands, query: Query<Entity, With<OnTentacle>>) {
@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    time::common_conditions::on_timer,
+    utils::HashMap,
+};
+use blenvy::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    interpolation::Interpolated,
+    weather::{CloudCover, Wind},
+    CLOUDS,
+};
+
+const LAYERS_PATH: &str = "cloud_layers.clouds.json";
+
+/// One band of clouds. Clouds drift downwind at `drift` units a second and are pushed by
+/// gusts in proportion to `parallax`, so far layers crawl while near ones race past.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CloudLayer {
+    pub depth: f32,
+    pub drift: f32,
+    pub parallax: f32,
+    pub heights: [f32; 2],
+    pub scale: [f32; 2],
+    pub stretch: [f32; 2],
+    pub tint: [f32; 3],
+    /// Clouds in the layer at a density of 1.
+    pub count: usize,
+    /// Chance each second of a new cloud while the layer is below its count.
+    pub spawn_chance: f32,
+}
+
+/// Clouds are culled once they pass the downwind edge and spawn past the upwind edge, a
+/// random distance within `spawn_margin` beyond it.
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug)]
+pub struct CloudLayers {
+    pub edges: [f32; 2],
+    pub spawn_margin: [f32; 2],
+    pub layers: Vec<CloudLayer>,
+}
+
+#[derive(Default)]
+struct CloudLayersLoader;
+
+impl AssetLoader for CloudLayersLoader {
+    type Asset = CloudLayers;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["clouds.json"]
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Cloud;
+
+/// Index of the layer a cloud belongs to.
+#[derive(Component)]
+struct CloudLayerIndex(usize);
+
+#[derive(Resource)]
+struct CloudsContainer(Entity);
+
+#[derive(Resource)]
+struct CloudLayersHandle(Handle<CloudLayers>);
+
+pub struct CloudsPlugin;
+
+impl Plugin for CloudsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<CloudLayers>()
+            .init_asset_loader::<CloudLayersLoader>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    maintain_clouds.run_if(on_timer(Duration::from_millis(1000))),
+                    tint_clouds,
+                ),
+            )
+            .add_systems(FixedUpdate, move_clouds);
+    }
+}
+
+fn setup(mut commands: Commands, assets: Res<AssetServer>) {
+    let id = commands.spawn(()).id();
+    commands.insert_resource(CloudsContainer(id));
+    commands.insert_resource(CloudLayersHandle(assets.load(LAYERS_PATH)));
+}
+
+fn maintain_clouds(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform, &CloudLayerIndex), With<Cloud>>,
+    clouds: Res<CloudsContainer>,
+    handle: Res<CloudLayersHandle>,
+    layers: Res<Assets<CloudLayers>>,
+    cover: Res<CloudCover>,
+) {
+    let Some(layers) = layers.get(&handle.0) else {
+        return;
+    };
+    let (upwind, downwind) = if cover.wind_direction < 0.0 {
+        (layers.edges[1], layers.edges[0])
+    } else {
+        (layers.edges[0], layers.edges[1])
+    };
+
+    let mut counts = vec![0; layers.layers.len()];
+    for (entity, transform, layer) in &query {
+        let past = (transform.translation.x - downwind) * cover.wind_direction;
+        if past > 0.0 || layer.0 >= counts.len() {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            counts[layer.0] += 1;
+        }
+    }
+
+    for (i, layer) in layers.layers.iter().enumerate() {
+        let target = (layer.count as f32 * cover.density).round() as usize;
+        if counts[i] >= target || fastrand::f32() >= layer.spawn_chance {
+            continue;
+        }
+        let range = |[min, max]: [f32; 2]| min + fastrand::f32() * (max - min);
+        let mut scale = Vec3::splat(range(layer.scale));
+        scale.x *= range(layer.stretch);
+        let x = upwind - range(layers.spawn_margin) * cover.wind_direction;
+        let transform = Transform::from_xyz(x, range(layer.heights), layer.depth).with_scale(scale);
+        let path = CLOUDS[fastrand::usize(0..CLOUDS.len())];
+        commands.entity(clouds.0).with_children(|commands| {
+            commands.spawn((
+                transform,
+                BlueprintInfo::from_path(path),
+                SpawnBlueprint,
+                HideUntilReady,
+                GameWorldTag,
+                Interpolated::default(),
+                Cloud,
+                CloudLayerIndex(i),
+            ));
+        });
+    }
+}
+
+fn move_clouds(
+    mut query: Query<(&mut Transform, &CloudLayerIndex), With<Cloud>>,
+    handle: Res<CloudLayersHandle>,
+    layers: Res<Assets<CloudLayers>>,
+    cover: Res<CloudCover>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+) {
+    let Some(layers) = layers.get(&handle.0) else {
+        return;
+    };
+    for (mut transform, index) in &mut query {
+        let Some(layer) = layers.layers.get(index.0) else {
+            continue;
+        };
+        transform.translation.x +=
+            (cover.wind_direction * layer.drift + wind.0 * layer.parallax) * time.delta_seconds();
+    }
+}
+
+/// Gives each layer its own copies of the cloud materials, tinted, once a cloud's blueprint
+/// has spawned.
+fn tint_clouds(
+    mut commands: Commands,
+    clouds: Query<(Entity, &CloudLayerIndex), Added<BlueprintInstanceReady>>,
+    children: Query<&Children>,
+    meshes: Query<&Handle<StandardMaterial>>,
+    handle: Res<CloudLayersHandle>,
+    layers: Res<Assets<CloudLayers>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tinted: Local<HashMap<(usize, AssetId<StandardMaterial>), Handle<StandardMaterial>>>,
+) {
+    let Some(layers) = layers.get(&handle.0) else {
+        return;
+    };
+    for (cloud, index) in &clouds {
+        let Some(layer) = layers.layers.get(index.0) else {
+            continue;
+        };
+        let [r, g, b] = layer.tint;
+        let tint = |c: LinearRgba| LinearRgba::new(c.red * r, c.green * g, c.blue * b, c.alpha);
+        for entity in std::iter::once(cloud).chain(children.iter_descendants(cloud)) {
+            let Ok(original) = meshes.get(entity) else {
+                continue;
+            };
+            let handle = match tinted.get(&(index.0, original.id())) {
+                Some(handle) => handle.clone(),
+                None => {
+                    let Some(mut material) = materials.get(original).cloned() else {
+                        continue;
+                    };
+                    material.base_color = tint(material.base_color.to_linear()).into();
+                    material.emissive = tint(material.emissive);
+                    let handle = materials.add(material);
+                    tinted.insert((index.0, original.id()), handle.clone());
+                    handle
+                }
+            };
+            commands.entity(entity).insert(handle);
+        }
+    }
+}
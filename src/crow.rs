@@ -1,22 +1,21 @@
-use std::{
-    time::Duration,
-    collections::{HashMap, BinaryHeap},
-};
+use std::time::Duration;
 
-use bevy::{
-    prelude::*,
-    time::common_conditions::on_timer,
-};
+use bevy::prelude::*;
 use blenvy::*;
 use bevy_mod_picking::prelude::*;
 
 use crate::{
-    Spawned, SpawnedFrom, build_phase::{Retracting, OnTentacle},
-    environmental_decoration::TimeOfDay,
+    MousePos, Spawned, SpawnedFrom, build_phase::{place_block, Retracting, OnTentacle, Snapped},
+    achievements::BlockPlaced,
+    crow_jobs::{CrowJob, CrowJobKind},
+    crow_profiles::{ActiveCrowProfile, CrowProfile, CrowProfiles},
+    decay_phase::{DecayCause, Decayed, ReplacedBy},
     build_phase::AwaitingPlacement,
-    block::Block,
+    block::{AnchorColor, AnchorState, Anchors, Block},
     block_pool::BlockPoolResident,
-    DECORATIONS,
+    interpolation::{sim_steps, Interpolated},
+    weather::Wind,
+    RoundRng, WorldClicks, DECORATIONS,
 };
 
 
@@ -31,12 +30,41 @@ pub struct CrowParams {
     speed: f32,
     max_speed: f32,
     target: Vec3,
+    /// Crows only notice flockmates within roughly this distance.
+    neighbor_radius: f32,
+    count: usize,
+}
+
+impl CrowParams {
+    /// Takes on `profile`'s flocking behaviour, picking a fresh target if `retarget`.
+    pub fn apply(&mut self, profile: &CrowProfile, retarget: bool) {
+        self.avoidance = profile.avoidance;
+        self.alignment = profile.alignment;
+        self.cohesion = profile.cohesion;
+        self.friction = profile.friction;
+        self.avoid_distance = profile.avoid_distance;
+        self.speed = profile.speed;
+        self.max_speed = profile.max_speed;
+        self.neighbor_radius = profile.neighbor_radius;
+        self.count = profile.count;
+        if retarget {
+            self.retarget(profile);
+        }
+    }
+
+    fn retarget(&mut self, profile: &CrowProfile) {
+        let [min_x, min_y] = profile.target_min;
+        let [max_x, max_y] = profile.target_max;
+        self.target.x = min_x + fastrand::f32() * (max_x - min_x);
+        self.target.y = min_y + fastrand::f32() * (max_y - min_y);
+    }
 }
 
 
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-struct CrowPerch;
+pub struct CrowPerch;
 
 #[derive(Component)]
 struct Velocity(Vec2);
@@ -58,13 +86,41 @@ pub struct Flying;
 pub struct Perching;
 
 #[derive(Component)]
-pub struct CrowPickupTarget;
+pub struct CrowTakeawayTarget;
 
+/// A crow settled on a `CrowPerch` of a ruined `block`.
 #[derive(Component)]
-pub struct CrowTakeawayTarget;
+pub struct Perched {
+    pub perch: Entity,
+    pub block: Entity,
+}
+
+/// Whether crows snap their decorations onto a free anchor themselves, rather than hovering
+/// for the player to take them. Toggled from the settings panel or with D.
+#[derive(Resource)]
+pub struct DirectDelivery(pub bool);
+
+/// A block a crow is making off with, and where it was taken from.
+#[derive(Component)]
+pub struct Stolen(Transform);
 
+/// Recently frightened off a roost, won't settle again until the timer runs out.
 #[derive(Component)]
-struct CrowAssigned;
+pub struct Scattered(Timer);
+
+/// Where crows leave the screen to fetch or get rid of blocks.
+pub const EXIT: Vec2 = Vec2::new(0.0, 50.0);
+/// Roosting crows this close to a lightning strike take flight.
+const SCATTER_RADIUS: f32 = 10.0;
+const SCATTER_COOLDOWN: Duration = Duration::from_secs(6);
+/// How strongly the wind blows the flock off course.
+const WIND_PUSH: f32 = 0.05;
+/// Clicks this close to a thieving crow frighten it off.
+const SCARE_RADIUS: f32 = 4.0;
+/// Radius of the circle crows fly around a villager they're harassing.
+const HARASS_RADIUS: f32 = 2.0;
+/// Radians per second harassing crows travel around that circle.
+const HARASS_ORBIT_SPEED: f32 = 2.5;
 
 
 #[derive(Component, Reflect, PartialEq, Debug)]
@@ -73,20 +129,48 @@ pub enum Employed {
     ExitingToDeliver(Entity, Vec3),
     ExitingToPickup(Entity),
     Delivering(Entity, Vec3),
+    /// Carrying a decoration to the anchor it will be snapped onto.
+    Placing(Entity, Snapped),
     PickingUp(Entity),
+    /// Flying to `perch` on the ruined `block`.
+    Roosting(Entity, Entity),
+    Harassing(Entity),
+    /// Swooping on a loose block, then carrying it off through the exit while it holds a `Grab`.
+    Stealing(Entity),
+    /// Scared off by the player, won't come back until `Scattered` runs out.
+    Fleeing,
     ReturningToFlock,
 }
 
 impl Employed {
     fn next(&self) -> Option<Self> {
         match self {
-            Employed::ExitingToDeliver(e, v) => Some(Employed::Delivering(*e,*v)),
+            // Handled on arrival at the exit, where the decoration is fetched
+            Employed::ExitingToDeliver(..) => None,
             Employed::ExitingToPickup(e) => Some(Employed::PickingUp(*e)),
             Employed::Delivering(..) => Some(Employed::ReturningToFlock),
+            Employed::Placing(..) => Some(Employed::ReturningToFlock),
             Employed::PickingUp(..) => Some(Employed::ReturningToFlock),
+            Employed::Roosting(..) => None,
+            Employed::Harassing(..) => None,
+            Employed::Stealing(..) => Some(Employed::ReturningToFlock),
+            Employed::Fleeing => None,
             Employed::ReturningToFlock => None,
         }
     }
+
+    /// The entity holding the `CrowJob` this crow is working on, if any.
+    pub fn target(&self) -> Option<Entity> {
+        match self {
+            Employed::ExitingToDeliver(e, _)
+            | Employed::ExitingToPickup(e)
+            | Employed::PickingUp(e)
+            | Employed::Roosting(e, _)
+            | Employed::Harassing(e)
+            | Employed::Stealing(e) => Some(*e),
+            Employed::Delivering(..) | Employed::Placing(..) | Employed::Fleeing | Employed::ReturningToFlock => None,
+        }
+    }
 }
 
 pub struct CrowPlugin;
@@ -98,6 +182,7 @@ impl Plugin for CrowPlugin {
             .register_type::<CrowPerch>()
             .register_type::<Flying>()
             .register_type::<Perching>()
+            .insert_resource(DirectDelivery(true))
             .insert_resource(CrowParams {
                 avoidance: 0.3,
                 alignment: 0.3,
@@ -107,27 +192,45 @@ impl Plugin for CrowPlugin {
                 speed: 0.05,
                 max_speed: 0.75,
                 target: Vec3::ZERO,
+                neighbor_radius: 12.0,
+                count: 10,
             })
             .add_systems(
-                OnEnter(crate::GameState::BuildPhase),
-                spawn_crows,
+                Update,
+                maintain_crow_count.run_if(not(in_state(crate::GameState::Loading))),
             )
             .add_systems(
-                PreUpdate,
-                (control_crow_visual, perturb_crows, steer_crows, steer_employed_crows, assign_crow_jobs, cleanup_crow_jobs).run_if(on_timer(Duration::from_millis(16)))
+                FixedUpdate,
+                (
+                    (perturb_crows, steer_crows, steer_employed_crows, cleanup_crow_jobs),
+                    (control_perched_crows, control_roosting_crows, move_crows),
+                ).chain()
             )
+            .add_systems(Update, control_crow_visual)
             .add_systems(
                 Update,
-                (control_perched_crows, move_crows).run_if(on_timer(Duration::from_millis(16)))
+                scatter_roosting_crows,
             )
+            .add_systems(
+                Update,
+                (
+                    scare_thieves.run_if(in_state(crate::GameState::BuildPhase)),
+                    toggle_direct_delivery,
+                ),
+            )
+            .add_systems(OnExit(crate::GameState::BuildPhase), abandon_thefts)
         ;
     }
 }
 
-fn spawn_crows(
+fn maintain_crow_count(
     mut commands: Commands,
+    crows: Query<Entity, (With<Crow>, Without<Employed>, Without<Grab>, Without<Perched>)>,
+    all_crows: Query<(), With<Crow>>,
+    params: Res<CrowParams>,
 ) {
-    for _ in 0..10 {
+    let count = all_crows.iter().count();
+    for _ in count..params.count {
         let transform = Transform::from_translation(Vec3::new(fastrand::i32(-60..-40) as f32, fastrand::i32(0..23) as f32, -7.1));
         commands.spawn((
             transform,
@@ -136,106 +239,128 @@ fn spawn_crows(
             HideUntilReady,
             GameWorldTag,
             Velocity(Vec2::ZERO),
+            Interpolated::default(),
             Crow,
         ));
     }
+    for entity in crows.iter().take(count.saturating_sub(params.count)) {
+        commands.entity(entity).despawn_recursive();
+    }
 }
 
 fn move_crows(
     mut query: Query<(&mut Transform, &Velocity)>,
+    time: Res<Time>,
 ) {
+    let steps = sim_steps(&time);
     for (mut t, v) in &mut query {
-        t.translation.x += v.0.x;
-        t.translation.y += v.0.y;
+        t.translation.x += v.0.x * steps;
+        t.translation.y += v.0.y * steps;
     }
 }
 
-struct NeighborhoodEntry(f32, Entity, Vec3, Vec2);
-impl PartialEq for NeighborhoodEntry {
-    fn eq(&self, other: &Self) -> bool {
-        ordered_float::OrderedFloat(self.0) == ordered_float::OrderedFloat(other.0)
-    }
-}
-impl Eq for NeighborhoodEntry {}
+/// How many of its nearest flockmates each crow steers by.
+const NEIGHBORS: usize = 3;
 
-impl PartialOrd for NeighborhoodEntry {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-impl Ord for NeighborhoodEntry {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        if self == other {
-            std::cmp::Ordering::Equal
-        } else {
-            ordered_float::OrderedFloat(self.0).cmp(&ordered_float::OrderedFloat(other.0))
-        }
-    }
+/// Crows bucketed into square cells `cell` wide, kept sorted by cell so that each cell's crows
+/// are a contiguous run. Reused between ticks so steering doesn't allocate.
+#[derive(Default)]
+struct CrowGrid {
+    cell: f32,
+    crows: Vec<(IVec2, Entity, Vec3, Vec2)>,
 }
 
-impl From<(f32, Entity, Vec3, Vec2)> for NeighborhoodEntry {
-    fn from(v: (f32, Entity, Vec3, Vec2)) -> Self {
-        NeighborhoodEntry(v.0, v.1, v.2, v.3)
+impl CrowGrid {
+    fn cell_of(&self, p: Vec3) -> IVec2 {
+        (p.xy() / self.cell).floor().as_ivec2()
     }
-}
-
-struct Neighborhood {
-    furthrest: f32,
-    entities: BinaryHeap<NeighborhoodEntry>,
-}
 
+    fn rebuild(&mut self, cell: f32, crows: impl Iterator<Item = (Entity, Vec3, Vec2)>) {
+        self.cell = cell.max(0.01);
+        self.crows.clear();
+        for (entity, translation, velocity) in crows {
+            let key = self.cell_of(translation);
+            self.crows.push((key, entity, translation, velocity));
+        }
+        self.crows.sort_unstable_by_key(|(key, ..)| (key.y, key.x));
+    }
 
-impl Default for Neighborhood {
-    fn default() -> Self {
-        Self {
-            furthrest: std::f32::INFINITY,
-            entities: BinaryHeap::new(),
+    /// The `NEIGHBORS` nearest crows to `entity` within the surrounding 3x3 cells, as
+    /// (distance, position, velocity).
+    fn nearest(&self, entity: Entity, p: Vec3) -> ([(f32, Vec3, Vec2); NEIGHBORS], usize) {
+        let mut found = [(f32::INFINITY, Vec3::ZERO, Vec2::ZERO); NEIGHBORS];
+        let mut count = 0;
+        let center = self.cell_of(p);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let key = center + IVec2::new(dx, dy);
+                let start = self
+                    .crows
+                    .partition_point(|(k, ..)| (k.y, k.x) < (key.y, key.x));
+                for (k, other, translation, velocity) in &self.crows[start..] {
+                    if *k != key {
+                        break;
+                    }
+                    if *other == entity {
+                        continue;
+                    }
+                    let d = (p - *translation).length();
+                    if count < NEIGHBORS {
+                        found[count] = (d, *translation, *velocity);
+                        count += 1;
+                    } else if let Some(furthest) = found
+                        .iter_mut()
+                        .max_by(|a, b| a.0.total_cmp(&b.0))
+                        .filter(|f| f.0 > d)
+                    {
+                        *furthest = (d, *translation, *velocity);
+                    }
+                }
+            }
         }
+        (found, count)
     }
 }
 
 fn steer_crows(
-    mut query: Query<(Entity, &Transform, &mut Velocity), Without<Employed>>,
-    mut neighborhoods: Local<HashMap<Entity, Neighborhood>>,
+    mut query: Query<(Entity, &Transform, &mut Velocity), (Without<Employed>, Without<Perched>)>,
+    mut grid: Local<CrowGrid>,
     params: Res<CrowParams>,
+    wind: Res<Wind>,
+    time: Res<Time>,
 ) {
-    for (e,t,_) in &query {
-        for (e2, t2, v2) in &query {
-            if e==e2 {
-                continue
-            }
-            let d = (t.translation - t2.translation).length();
-            let entry = neighborhoods.entry(e).or_default();
-            entry.entities.push((d, e2, t2.translation, v2.0).into());
-            if entry.entities.len() > 3 {
-                entry.entities.pop();
-            }
-        }
-    }
-
-    for (entity, neighborhood) in neighborhoods.drain() {
-        let (_, t, mut v) = query.get_mut(entity).unwrap();
+    // Velocities are per step at SIM_HZ
+    let steps = sim_steps(&time);
+    let speed = params.speed * steps;
+    grid.rebuild(
+        params.neighbor_radius,
+        query.iter().map(|(e, t, v)| (e, t.translation, v.0)),
+    );
+
+    for (entity, t, mut v) in &mut query {
+        let (neighbors, count) = grid.nearest(entity, t.translation);
         let mut avg_vel = Vec2::ZERO;
         let mut alignment_count = 0;
         let mut center_of_mass = Vec3::ZERO;
-        for entry in neighborhood.entities {
-            if entry.0 < params.avoid_distance {
-                let d = (t.translation - entry.2) * params.avoidance;
-                v.0 += d.xy() * params.speed;
+        for (d, translation, velocity) in &neighbors[..count] {
+            if *d < params.avoid_distance {
+                let d = (t.translation - *translation) * params.avoidance;
+                v.0 += d.xy() * speed;
             } else {
                 alignment_count += 1;
-                avg_vel += entry.3;
-                center_of_mass += entry.2;
+                avg_vel += *velocity;
+                center_of_mass += *translation;
             }
         }
         if alignment_count > 0 {
             let nv = (avg_vel/alignment_count as f32 - v.0) * params.alignment;
-            v.0 += nv * params.speed;
+            v.0 += nv * speed;
         }
         center_of_mass += params.target*1.0;
         alignment_count += 1;
-        v.0 += ((center_of_mass/alignment_count as f32 - t.translation).xy()) * params.cohesion * params.speed;
-        v.0 *= params.friction;
+        v.0 += ((center_of_mass/alignment_count as f32 - t.translation).xy()) * params.cohesion * speed;
+        v.0.x += wind.0 * WIND_PUSH * time.delta_seconds();
+        v.0 *= params.friction.powf(steps);
         v.0 = v.0.clamp(-Vec2::splat(params.max_speed), Vec2::splat(params.max_speed));
     }
 }
@@ -244,24 +369,56 @@ fn steer_employed_crows(
     mut commands: Commands,
     mut query: Query<(Entity, &mut Transform, &mut Velocity, &mut Employed), Without<CrowTakeawayTarget>>,
     mut takeaways: Query<(&mut Transform, &SpawnedFrom), With<CrowTakeawayTarget>>,
+    mut loose: Query<&mut Transform, (With<Block>, Without<Employed>, Without<CrowTakeawayTarget>)>,
+    hanging: Query<(), With<OnTentacle>>,
+    dragging: Query<(), With<AwaitingPlacement>>,
+    placed: Query<
+        (Entity, &GlobalTransform),
+        (With<Block>, Without<OnTentacle>, Without<BlockPoolResident>, Without<Stolen>),
+    >,
+    mut anchors: Query<&mut Anchors>,
+    children: Query<&Children>,
+    mut placed_events: EventWriter<BlockPlaced>,
     block_pool: Query<(Entity, &BlockPoolResident)>,
     transforms: Query<&GlobalTransform>,
     params: Res<CrowParams>,
+    direct_delivery: Res<DirectDelivery>,
+    time: Res<Time>,
+    mut rng: ResMut<RoundRng>,
 ) {
+    let speed = params.speed * sim_steps(&time);
     for (crow_entity, mut t, mut v, mut employment) in &mut query {
         let mut speed_mul = 2.5;
+        if *employment == Employed::Fleeing {
+            speed_mul = 4.0;
+        }
         match &*employment {
-            Employed::ExitingToDeliver(_, _) | Employed::ExitingToPickup(_) | Employed::ReturningToFlock => {
-                if &*employment == &Employed::ReturningToFlock {
+            Employed::ExitingToDeliver(_, _) | Employed::ExitingToPickup(_) | Employed::ReturningToFlock | Employed::Fleeing => {
+                if matches!(*employment, Employed::ReturningToFlock | Employed::Fleeing) {
                     t.scale = Vec3::splat(5.0);
                     t.translation.z = 1.0;
                 } else {
                     t.scale = Vec3::splat(1.0);
                     t.translation.z = -7.1;
                 }
-                v.0 += (Vec2::new(0.0, 50.0)-t.translation.xy())*params.speed*speed_mul;
-                if (t.translation.xy() - Vec2::new(0.0, 50.0)).length() < 10.0 {
-                    if let Some(next) = employment.next() {
+                v.0 += (EXIT-t.translation.xy())*speed*speed_mul;
+                if (t.translation.xy() - EXIT).length() < 10.0 {
+                    if let Employed::ExitingToDeliver(point, target) = *employment {
+                        commands.entity(point).despawn_recursive();
+                        *employment = match fetch_decoration(&mut commands, &block_pool, crow_entity, &mut rng.0) {
+                            Some(decoration) => {
+                                let snapped = direct_delivery
+                                    .0
+                                    .then(|| find_decoration_anchor(decoration, target, &anchors, &placed))
+                                    .flatten();
+                                match snapped {
+                                    Some(snapped) => Employed::Placing(decoration, snapped),
+                                    None => Employed::Delivering(decoration, target),
+                                }
+                            }
+                            None => Employed::ReturningToFlock,
+                        };
+                    } else if let Some(next) = employment.next() {
                         *employment = next;
                     } else {
                         t.scale = Vec3::splat(1.0);
@@ -270,20 +427,124 @@ fn steer_employed_crows(
                     }
                 }
             }
+            Employed::Roosting(perch, block) => {
+                t.scale = Vec3::splat(5.0);
+                t.translation.z = 1.0;
+                if let Ok(target) = transforms.get(*perch) {
+                    let d = target.translation().xy() - t.translation.xy();
+                    if d.length() < 1.0 {
+                        commands
+                            .entity(crow_entity)
+                            .remove::<Employed>()
+                            .insert(Perched { perch: *perch, block: *block });
+                        v.0 = Vec2::ZERO;
+                        continue;
+                    }
+                    v.0 += d*speed*speed_mul;
+                }
+            }
+            Employed::Harassing(villager) => {
+                t.scale = Vec3::splat(5.0);
+                t.translation.z = 1.0;
+                if let Ok(target) = transforms.get(*villager) {
+                    // Circle a little above the villager, each crow starting at its own angle
+                    let angle = crow_entity.index() as f32 + time.elapsed_seconds() * HARASS_ORBIT_SPEED;
+                    let center = target.translation().xy() + Vec2::Y * HARASS_RADIUS;
+                    let orbit = center + Vec2::from_angle(angle) * HARASS_RADIUS;
+                    v.0 += (orbit - t.translation.xy())*speed*speed_mul;
+                }
+            }
+            Employed::Stealing(block) => {
+                t.scale = Vec3::splat(5.0);
+                t.translation.z = 1.0;
+                if let Ok((mut takeaway, spawned_from)) = takeaways.get_mut(*block) {
+                    if (t.translation.xy() - EXIT).length() < 10.0 {
+                        if hanging.contains(*block) {
+                            commands
+                                .entity(spawned_from.0)
+                                .remove::<Spawned>()
+                                .insert(Retracting);
+                        } else {
+                            release_anchors(*block, &mut anchors);
+                        }
+                        commands.entity(*block).despawn_recursive();
+                        commands.entity(crow_entity).remove::<Grab>();
+                        *employment = Employed::ReturningToFlock;
+                    } else {
+                        v.0 += (EXIT-t.translation.xy())*speed*speed_mul;
+                    }
+                    takeaway.translation = t.translation;
+                } else if let (Ok(target), Ok(block_t)) = (transforms.get(*block), loose.get(*block)) {
+                    let d = target.translation().xy()-t.translation.xy();
+                    if d.length() < 4.0 {
+                        commands.entity(crow_entity).insert(Grab(*block));
+                        commands
+                            .entity(*block)
+                            .insert((CrowTakeawayTarget, Stolen(*block_t), Interpolated::default()));
+                        v.0 = Vec2::ZERO;
+                    } else {
+                        v.0 += d*speed*speed_mul*d.length().powi(2);
+                    }
+                }
+            }
             Employed::Delivering(_, target) => {
                 t.scale = Vec3::splat(5.0);
                 t.translation.z = 1.0;
                 if (target.xy()-t.translation.xy()).length() > 5.0 {
-                    v.0 += (target.xy()-t.translation.xy())*params.speed*(target.xy()-t.translation.xy()).length().powi(2);
+                    v.0 += (target.xy()-t.translation.xy())*speed*(target.xy()-t.translation.xy()).length().powi(2);
+                } else {
+                    v.0 = Vec2::ZERO;
+                }
+            }
+            Employed::Placing(decoration, snapped) => {
+                t.scale = Vec3::splat(5.0);
+                t.translation.z = 1.0;
+                let decoration = *decoration;
+                if dragging.contains(decoration) {
+                    v.0 = Vec2::ZERO;
+                    continue;
+                }
+                if !loose.contains(decoration) {
+                    *employment = Employed::ReturningToFlock;
+                    continue;
+                }
+                let snapped = if snap_open(snapped, &anchors, &placed) {
+                    Some(snapped.clone())
                 } else {
+                    find_decoration_anchor(decoration, t.translation, &anchors, &placed)
+                };
+                let Some(snapped) = snapped else {
+                    // Nowhere left to put it, hover for the player to take it instead
+                    *employment = Employed::Delivering(decoration, t.translation);
+                    continue;
+                };
+                let d = snapped.a_translation.xy() - t.translation.xy();
+                if d.length() < 1.0 {
+                    if let Ok(mut decoration_t) = loose.get_mut(decoration) {
+                        decoration_t.rotation = Quat::IDENTITY;
+                        place_block(
+                            &mut commands,
+                            decoration,
+                            crow_entity,
+                            &mut decoration_t,
+                            &snapped,
+                            &mut anchors,
+                            &children,
+                            &mut placed_events,
+                        );
+                    }
                     v.0 = Vec2::ZERO;
+                    *employment = Employed::ReturningToFlock;
+                } else {
+                    v.0 += d*speed*speed_mul;
+                    *employment = Employed::Placing(decoration, snapped);
                 }
             }
             Employed::PickingUp(e) => {
                 t.scale = Vec3::splat(5.0);
                 t.translation.z = 1.0;
                 if let Ok((mut takeaway, spawned_from)) = takeaways.get_mut(*e) {
-                    if (t.translation.xy() - Vec2::new(0.0, 50.0)).length() < 10.0 {
+                    if (t.translation.xy() - EXIT).length() < 10.0 {
                         commands.entity(*e).despawn_recursive();
                         commands
                             .entity(spawned_from.0)
@@ -291,42 +552,19 @@ fn steer_employed_crows(
                             .insert(Retracting);
                         commands.entity(crow_entity).remove::<Grab>();
 
-                        let draw = fastrand::f32();
-                        let idx = DECORATIONS
-                         .iter()
-                         .position(|(_, p)| *p >= draw)
-                         .unwrap_or(DECORATIONS.len() - 1);
-                        let path = &DECORATIONS[idx].0;
-                        let mut found = None;
-                        for (entity, resident) in &block_pool {
-                         if &resident.0 == path {
-                             found = Some(entity);
-                             break;
-                         }
-                        }
-                        if let Some(entity) = found {
-                         commands
-                             .entity(entity)
-                             .insert((
-                                 OnTentacle,
-                                 Block,
-                                 Visibility::Visible,
-                                 SpawnedFrom(crow_entity),
-                                 PickableBundle::default(),
-                                 On::<Pointer<DragStart>>::listener_insert(AwaitingPlacement),
-                                 On::<Pointer<DragEnd>>::listener_remove::<AwaitingPlacement>(),
-                             ))
-                             .remove::<BlockPoolResident>();
-                         commands.entity(crow_entity).insert(Spawned(entity));
-                         let target = Vec3::new(
-                             fastrand::i32(-23..22) as f32,
-                             fastrand::i32(9..22) as f32,
-                             10.0
-                         );
-                         *employment = Employed::Delivering(entity, target);
-                        }
+                        // Something has to come back in its place
+                        let target = Vec3::new(
+                            fastrand::i32(-23..22) as f32,
+                            fastrand::i32(9..22) as f32,
+                            10.0
+                        );
+                        commands.spawn((
+                            TransformBundle::from_transform(Transform::from_translation(target)),
+                            CrowJob::new(CrowJobKind::Deliver),
+                        ));
+                        *employment = Employed::ReturningToFlock;
                     } else {
-                        v.0 += (Vec2::new(0.0, 50.0)-t.translation.xy())*params.speed*speed_mul;
+                        v.0 += (EXIT-t.translation.xy())*speed*speed_mul;
                     }
                     takeaway.translation = t.translation;
                 } else {
@@ -336,7 +574,7 @@ fn steer_employed_crows(
                             commands.entity(crow_entity).insert(Grab(*e));
                             v.0 = Vec2::ZERO;
                         } else {
-                            v.0 += d*params.speed*speed_mul*(target.translation().xy()-t.translation.xy()).length().powi(2);
+                            v.0 += d*speed*speed_mul*(target.translation().xy()-t.translation.xy()).length().powi(2);
                         }
                     }
                 }
@@ -348,24 +586,25 @@ fn steer_employed_crows(
 
 fn control_crow_visual(
     mut commands: Commands,
-    query: Query<(Entity, Option<&Grab>), With<Crow>>,
+    query: Query<(Entity, Option<&Grab>, Has<Perched>), With<Crow>>,
     takeaways: Query<&Transform, With<CrowTakeawayTarget>>,
     children: Query<&Children>,
     flying: Query<Entity, With<Flying>>,
-    perching: Query<Entity, With<Perching>>,
+    perching_visuals: Query<Entity, With<Perching>>,
 ) {
-    for (entity, grab) in &query {
+    for (entity, grab, perched) in &query {
         let taking_away = grab.as_ref().and_then(|g| Some(takeaways.contains(g.0))).unwrap_or(false);
+        let perching = (grab.is_some() && !taking_away) || perched;
         for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
             if let Ok(entity) = flying.get(entity) {
-                if grab.is_some() && !taking_away {
+                if perching {
                     commands.entity(entity).insert(Visibility::Hidden);
                 } else {
                     commands.entity(entity).insert(Visibility::Visible);
                 }
             }
-            if let Ok(entity) = perching.get(entity) {
-                if grab.is_some() && !taking_away {
+            if let Ok(entity) = perching_visuals.get(entity) {
+                if perching {
                     commands.entity(entity).insert(Visibility::Visible);
                 } else {
                     commands.entity(entity).insert(Visibility::Hidden);
@@ -397,37 +636,266 @@ fn control_perched_crows(
     }
 }
 
-fn assign_crow_jobs(
+fn control_roosting_crows(
+    mut query: Query<(&mut Transform, &mut Velocity, &Perched)>,
+    perches: Query<&GlobalTransform, With<CrowPerch>>,
+) {
+    for (mut crow_t, mut v, perched) in &mut query {
+        if let Ok(perch_t) = perches.get(perched.perch) {
+            let (_scale, rotation, translation) = perch_t.to_scale_rotation_translation();
+            crow_t.translation = translation;
+            crow_t.translation.z += 2.0;
+            crow_t.rotation = rotation;
+            v.0 = Vec2::ZERO;
+        }
+    }
+}
+
+fn scatter_roosting_crows(
+    mut commands: Commands,
+    strikes: Query<(&GlobalTransform, &DecayCause), Or<(Added<Decayed>, Added<ReplacedBy>)>>,
+    mut crows: Query<(Entity, &Transform, &mut Velocity, Option<&Employed>, Has<Perched>), With<Crow>>,
+    mut scattered: Query<(Entity, &mut Scattered)>,
+    params: Res<CrowParams>,
+    time: Res<Time>,
+) {
+    for (entity, mut scattered) in &mut scattered {
+        if scattered.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Scattered>();
+        }
+    }
+
+    for (strike, cause) in &strikes {
+        if *cause != DecayCause::Lightning {
+            continue;
+        }
+        for (entity, t, mut v, employed, perched) in &mut crows {
+            let roosting = perched || matches!(employed, Some(Employed::Roosting(..)));
+            let away = t.translation.xy() - strike.translation().xy();
+            if !roosting || away.length() > SCATTER_RADIUS {
+                continue;
+            }
+            v.0 = away.normalize_or(Vec2::Y) * params.max_speed * 2.5;
+            commands
+                .entity(entity)
+                .remove::<Perched>()
+                .insert((
+                    Employed::ReturningToFlock,
+                    Scattered(Timer::new(SCATTER_COOLDOWN, TimerMode::Once)),
+                ));
+        }
+    }
+}
+
+/// Takes a random decoration out of the block pool and hangs it from `crow`.
+fn fetch_decoration(
+    commands: &mut Commands,
+    block_pool: &Query<(Entity, &BlockPoolResident)>,
+    crow: Entity,
+    rng: &mut fastrand::Rng,
+) -> Option<Entity> {
+    let draw = rng.f32();
+    let idx = DECORATIONS
+        .iter()
+        .position(|(_, p)| *p >= draw)
+        .unwrap_or(DECORATIONS.len() - 1);
+    let path = &DECORATIONS[idx].0;
+    let (entity, _) = block_pool.iter().find(|(_, resident)| &resident.0 == path)?;
+    commands
+        .entity(entity)
+        .insert((
+            OnTentacle,
+            Block,
+            Visibility::Visible,
+            SpawnedFrom(crow),
+            PickableBundle::default(),
+            On::<Pointer<DragStart>>::listener_insert(AwaitingPlacement),
+            On::<Pointer<DragEnd>>::listener_remove::<AwaitingPlacement>(),
+        ))
+        .remove::<BlockPoolResident>();
+    commands.entity(crow).insert(Spawned(entity));
+    Some(entity)
+}
+
+/// Whether both anchors `snapped` would join are still free, on a block that's still in place.
+fn snap_open(
+    snapped: &Snapped,
+    anchors: &Query<&mut Anchors>,
+    placed: &Query<
+        (Entity, &GlobalTransform),
+        (With<Block>, Without<OnTentacle>, Without<BlockPoolResident>, Without<Stolen>),
+    >,
+) -> bool {
+    let clear = |entity: Entity, anchor: usize| {
+        anchors
+            .get(entity)
+            .is_ok_and(|a| a.0.get(anchor).is_some_and(|(_, _, state, _)| *state == AnchorState::Clear))
+    };
+    placed.contains(snapped.b_entity)
+        && clear(snapped.a_entity, snapped.a_anchor)
+        && clear(snapped.b_entity, snapped.b_anchor)
+}
+
+/// Finds the free `DecorationUp` anchor nearest `near` that `decoration` could be snapped onto.
+fn find_decoration_anchor(
+    decoration: Entity,
+    near: Vec3,
+    anchors: &Query<&mut Anchors>,
+    placed: &Query<
+        (Entity, &GlobalTransform),
+        (With<Block>, Without<OnTentacle>, Without<BlockPoolResident>, Without<Stolen>),
+    >,
+) -> Option<Snapped> {
+    let decoration_anchors = anchors.get(decoration).ok()?;
+    let mut best: Option<(f32, Snapped)> = None;
+    for (a_anchor, (anchor, color, state, _)) in decoration_anchors.0.iter().enumerate() {
+        if *state != AnchorState::Clear {
+            continue;
+        }
+        for (block, block_t) in placed {
+            if block == decoration {
+                continue;
+            }
+            let Ok(block_anchors) = anchors.get(block) else {
+                continue;
+            };
+            for (b_anchor, (other_anchor, other_color, other_state, _)) in block_anchors.0.iter().enumerate() {
+                if *other_color != AnchorColor::DecorationUp
+                    || *other_state != AnchorState::Clear
+                    || !color.compatible(*other_color)
+                {
+                    continue;
+                }
+                let mut a_translation = block_t.translation() + *other_anchor - *anchor;
+                a_translation.z = block_t.translation().z;
+                let d = a_translation.xy().distance(near.xy());
+                if best.as_ref().map_or(true, |(best_d, _)| d < *best_d) {
+                    best = Some((d, Snapped {
+                        a_entity: decoration,
+                        a_anchor,
+                        b_entity: block,
+                        b_anchor,
+                        a_translation,
+                    }));
+                }
+            }
+        }
+    }
+    best.map(|(_, snapped)| snapped)
+}
+
+/// Frees every anchor `block` occupies or blocks on its neighbours.
+fn release_anchors(block: Entity, anchors: &mut Query<&mut Anchors>) {
+    let neighbours: Vec<Entity> = anchors
+        .get(block)
+        .map(|a| {
+            a.0.iter()
+                .filter_map(|(_, _, state, _)| match state {
+                    AnchorState::Occupied(e) | AnchorState::Blocked(e) => Some(*e),
+                    AnchorState::Clear => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    for neighbour in neighbours {
+        if let Ok(mut other) = anchors.get_mut(neighbour) {
+            for (_, _, state, _) in other.0.iter_mut() {
+                if matches!(*state, AnchorState::Occupied(e) | AnchorState::Blocked(e) if e == block) {
+                    *state = AnchorState::Clear;
+                }
+            }
+        }
+    }
+}
+
+/// Lets go of whatever `crow` was making off with, putting it back where it was taken from.
+fn drop_loot(
+    commands: &mut Commands,
+    crow: Entity,
+    grab: Option<&Grab>,
+    loot: &mut Query<(&mut Transform, &Stolen), Without<Employed>>,
+) {
+    let Some(Grab(block)) = grab else {
+        return;
+    };
+    commands.entity(crow).remove::<Grab>();
+    if let Ok((mut transform, stolen)) = loot.get_mut(*block) {
+        *transform = stolen.0;
+        commands
+            .entity(*block)
+            .remove::<(Stolen, CrowTakeawayTarget, Interpolated)>();
+    }
+}
+
+/// Thieves put back whatever they're carrying once the build phase is over.
+fn abandon_thefts(
+    mut commands: Commands,
+    mut crows: Query<(Entity, &mut Employed, Option<&Grab>)>,
+    mut loot: Query<(&mut Transform, &Stolen), Without<Employed>>,
+) {
+    for (entity, mut employment, grab) in &mut crows {
+        if matches!(*employment, Employed::Stealing(_)) {
+            drop_loot(&mut commands, entity, grab, &mut loot);
+            *employment = Employed::ReturningToFlock;
+        }
+    }
+}
+
+fn toggle_direct_delivery(keyboard: Res<ButtonInput<KeyCode>>, mut direct_delivery: ResMut<DirectDelivery>) {
+    if keyboard.just_pressed(KeyCode::KeyD) {
+        direct_delivery.0 = !direct_delivery.0;
+    }
+}
+
+fn scare_thieves(
     mut commands: Commands,
-    jobs: Query<Entity, (With<CrowPickupTarget>, Without<CrowAssigned>)>,
-    crows: Query<Entity, (With<Crow>, Without<Employed>)>,
+    clicks: WorldClicks,
+    mouse_pos: Res<MousePos>,
+    mut crows: Query<(Entity, &Transform, &mut Velocity, &mut Employed, Option<&Grab>)>,
+    mut loot: Query<(&mut Transform, &Stolen), Without<Employed>>,
+    params: Res<CrowParams>,
 ) {
-    for entity in &jobs {
-        if let Some(crow) = crows.iter().next() {
-            commands.entity(entity).insert(CrowAssigned);
-            commands.entity(crow).insert(Employed::ExitingToPickup(entity));
+    if !clicks.just_pressed() {
+        return;
+    }
+    for (entity, t, mut v, mut employment, grab) in &mut crows {
+        let away = t.translation.xy() - mouse_pos.0;
+        if !matches!(*employment, Employed::Stealing(_)) || away.length() > SCARE_RADIUS {
+            continue;
         }
+        drop_loot(&mut commands, entity, grab, &mut loot);
+        v.0 = away.normalize_or(Vec2::Y) * params.max_speed * 4.0;
+        *employment = Employed::Fleeing;
+        commands
+            .entity(entity)
+            .insert(Scattered(Timer::new(SCATTER_COOLDOWN, TimerMode::Once)));
     }
 }
 
 fn cleanup_crow_jobs(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Employed, &mut Transform, Option<&Velocity>, Option<&Spawned>)>,
-    jobs: Query<Entity, Or<(With<CrowPickupTarget>, With<CrowTakeawayTarget>)>>,
+    mut query: Query<(Entity, &mut Employed, &mut Transform, Option<&Velocity>, Option<&Spawned>, Option<&Grab>)>,
+    mut loot: Query<(&mut Transform, &Stolen), Without<Employed>>,
+    jobs: Query<Entity, Or<(With<CrowJob>, With<CrowTakeawayTarget>)>>,
+    perches: Query<(), With<CrowPerch>>,
+    perched: Query<(Entity, &Perched)>,
 ) {
-    for (e, mut employment, mut t, maybe_v, maybe_spawned) in &mut query {
+    for (e, perched) in &perched {
+        if !perches.contains(perched.perch) {
+            commands.entity(e).remove::<Perched>().insert(Employed::ReturningToFlock);
+        }
+    }
+    for (e, mut employment, mut t, maybe_v, maybe_spawned, maybe_grab) in &mut query {
         match &*employment {
             Employed::ExitingToPickup(target) => {
                 if !jobs.contains(*target) {
                     t.scale = Vec3::splat(1.0);
                     t.translation.z = -7.1;
                     commands.entity(e).remove::<Employed>();
-                    commands.entity(*target).remove::<CrowAssigned>();
                 }
             }
             Employed::PickingUp(target) => {
                 if !jobs.contains(*target) {
-                    commands.entity(*target).remove::<CrowAssigned>();
                     commands.entity(e).remove::<Grab>();
                     if maybe_v.is_none() {
                         commands.entity(e).insert(Velocity(default()));
@@ -435,11 +903,22 @@ fn cleanup_crow_jobs(
                     *employment = Employed::ReturningToFlock;
                 }
             }
-            Employed::Delivering(..) => {
+            Employed::Delivering(..) | Employed::Placing(..) => {
                 if maybe_spawned.is_none() {
                     *employment = Employed::ReturningToFlock;
                 }
             }
+            Employed::Stealing(target) => {
+                if !jobs.contains(*target) {
+                    drop_loot(&mut commands, e, maybe_grab, &mut loot);
+                    *employment = Employed::ReturningToFlock;
+                }
+            }
+            Employed::ExitingToDeliver(target, _) | Employed::Roosting(target, _) | Employed::Harassing(target) => {
+                if !jobs.contains(*target) {
+                    *employment = Employed::ReturningToFlock;
+                }
+            }
             _ => (),
         }
     }
@@ -447,31 +926,25 @@ fn cleanup_crow_jobs(
 
 fn perturb_crows(
     mut params: ResMut<CrowParams>,
-    mut avoidance_target: Local<Option<f32>>,
-    time_of_day: Res<TimeOfDay>,
-    mut last_time_of_day: Local<TimeOfDay>,
+    active: Res<ActiveCrowProfile>,
+    profiles: Res<Assets<CrowProfiles>>,
+    time: Res<Time>,
 ) {
-    if avoidance_target.is_none() {
-        *avoidance_target = Some(params.avoid_distance);
+    let dt = time.delta_seconds();
+    let Some(profile) = active
+        .kind
+        .and_then(|kind| Some(profiles.get(&active.handle)?.get(kind)))
+    else {
+        return;
+    };
+
+    if fastrand::f32() < profile.retarget_rate * dt {
+        params.retarget(profile);
     }
-
-    if *time_of_day == TimeOfDay::Day {
-        if fastrand::f32() < 1.0/(60.0*8.0) || *last_time_of_day != *time_of_day {
-            params.target.x = fastrand::i32(-40..34) as f32;
-            params.target.y = fastrand::i32(0..23) as f32;
-        }
-
-        if *last_time_of_day != *time_of_day {
-            params.avoid_distance = avoidance_target.unwrap_or(4.0);
-        } else if fastrand::f32() < 1.0/(60.0*8.0) {
-            params.avoid_distance = 10.0;
-        } else if params.avoid_distance > avoidance_target.unwrap_or(4.0) {
-            params.avoid_distance -= 0.05;
-        }
-    } else {
-        params.target.x = 74.0;
-        params.target.y = 70.0;
-        params.avoid_distance = 40.0;
+    if fastrand::f32() < profile.scatter_rate * dt {
+        params.avoid_distance = profile.scatter_distance;
+    } else if params.avoid_distance > profile.avoid_distance {
+        params.avoid_distance =
+            (params.avoid_distance - profile.scatter_recovery * dt).max(profile.avoid_distance);
     }
-    *last_time_of_day = *time_of_day;
 }
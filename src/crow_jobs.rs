@@ -0,0 +1,287 @@
+use bevy::prelude::*;
+
+use crate::{
+    block::{AnchorColor, AnchorState, Anchors, Block},
+    block_pool::BlockPoolResident,
+    build_phase::{AwaitingPlacement, OnTentacle},
+    crow::{Crow, CrowPerch, Employed, Grab, Perched, Scattered, Stolen, EXIT},
+    decay_phase::Decayed,
+    environmental_decoration::TimeOfDay,
+    villagers::{Fleeing, Villager},
+    GameState, RoundRng, SpawnedFrom, Spawner,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CrowJobKind {
+    /// Carry the target block off through the exit.
+    Pickup,
+    /// Fetch a decoration from the exit and bring it to the target's position.
+    Deliver,
+    /// Pester the target villager.
+    Harass,
+    /// Settle on the target perch of the ruined `block`.
+    Roost { block: Entity },
+    /// Make off with the target block.
+    Steal,
+}
+
+impl CrowJobKind {
+    pub fn priority(&self) -> i32 {
+        match self {
+            CrowJobKind::Pickup => 3,
+            CrowJobKind::Deliver => 2,
+            CrowJobKind::Roost { .. } | CrowJobKind::Steal => 1,
+            CrowJobKind::Harass => 0,
+        }
+    }
+}
+
+/// Something a crow should do, attached to the entity it should be done to. Higher priority
+/// jobs get first pick of the idle crows.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct CrowJob {
+    pub kind: CrowJobKind,
+    pub priority: i32,
+}
+
+impl CrowJob {
+    pub fn new(kind: CrowJobKind) -> Self {
+        Self {
+            kind,
+            priority: kind.priority(),
+        }
+    }
+}
+
+/// The crow currently working a job.
+#[derive(Component)]
+struct CrowAssigned(Entity);
+
+/// Optional build phase hazard where idle crows swoop in to steal loose blocks. Toggled from
+/// the settings panel or with T.
+#[derive(Resource, Default)]
+pub struct ThievingCrows(pub bool);
+
+/// Chance per second of a crow going after a block while thieving is on.
+const THEFT_RATE: f32 = 0.1;
+/// Chance per second that an idle crow goes looking for a roost at night, so the flock
+/// settles a crow at a time rather than all at once.
+const ROOST_RATE: f32 = 2.0;
+
+pub struct CrowJobPlugin;
+
+impl Plugin for CrowJobPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ThievingCrows>()
+            .add_systems(
+                PreUpdate,
+                (post_night_jobs, post_theft_jobs, release_jobs, schedule_jobs).chain(),
+            )
+            .add_systems(Update, toggle_thieving)
+            .add_systems(OnExit(GameState::BuildPhase), cancel_deliveries);
+    }
+}
+
+/// After dark crows roost on ruins and pester villagers who haven't fled yet. Once it gets
+/// light those jobs are withdrawn.
+fn post_night_jobs(
+    mut commands: Commands,
+    time_of_day: Res<TimeOfDay>,
+    jobs: Query<(Entity, &CrowJob)>,
+    perches: Query<Entity, (With<CrowPerch>, Without<CrowJob>)>,
+    parents: Query<&Parent>,
+    decayed: Query<(), With<Decayed>>,
+    villagers: Query<Entity, (With<Villager>, Without<Fleeing>, Without<CrowJob>)>,
+    fleeing: Query<(), With<Fleeing>>,
+) {
+    for (entity, job) in &jobs {
+        let withdrawn = match job.kind {
+            CrowJobKind::Harass => !time_of_day.is_dark() || fleeing.contains(entity),
+            CrowJobKind::Roost { .. } => !time_of_day.is_dark(),
+            _ => false,
+        };
+        if withdrawn {
+            commands.entity(entity).remove::<(CrowJob, CrowAssigned)>();
+        }
+    }
+    if !time_of_day.is_dark() {
+        return;
+    }
+
+    for perch in &perches {
+        if let Some(block) = parents.iter_ancestors(perch).find(|a| decayed.contains(*a)) {
+            commands
+                .entity(perch)
+                .insert(CrowJob::new(CrowJobKind::Roost { block }));
+        }
+    }
+    for villager in &villagers {
+        commands
+            .entity(villager)
+            .insert(CrowJob::new(CrowJobKind::Harass));
+    }
+}
+
+fn toggle_thieving(keyboard: Res<ButtonInput<KeyCode>>, mut thieving: ResMut<ThievingCrows>) {
+    if keyboard.just_pressed(KeyCode::KeyT) {
+        thieving.0 = !thieving.0;
+    }
+}
+
+/// A placed block resting on a single support with nothing on top of it.
+fn loosely_supported(anchors: &Anchors) -> bool {
+    let mut supports = 0;
+    for (_, color, state, _) in &anchors.0 {
+        if let AnchorState::Occupied(_) = state {
+            match color {
+                AnchorColor::Down | AnchorColor::DecorationDown => supports += 1,
+                AnchorColor::Up | AnchorColor::DecorationUp => return false,
+                AnchorColor::None => (),
+            }
+        }
+    }
+    supports == 1
+}
+
+/// While thieving is on, every so often marks a loose top block or one still hanging from a
+/// tentacle for stealing. Only one theft is attempted at a time.
+fn post_theft_jobs(
+    mut commands: Commands,
+    thieving: Res<ThievingCrows>,
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+    mut rng: ResMut<RoundRng>,
+    jobs: Query<(Entity, &CrowJob)>,
+    placed: Query<
+        (Entity, &Anchors),
+        (
+            With<Block>,
+            Without<OnTentacle>,
+            Without<BlockPoolResident>,
+            Without<Stolen>,
+        ),
+    >,
+    hanging: Query<(Entity, &SpawnedFrom), (With<OnTentacle>, Without<AwaitingPlacement>)>,
+    spawners: Query<(), With<Spawner>>,
+) {
+    let active = thieving.0 && *state.get() == GameState::BuildPhase;
+    let mut stealing = false;
+    for (entity, job) in &jobs {
+        if job.kind == CrowJobKind::Steal {
+            if active {
+                stealing = true;
+            } else {
+                commands.entity(entity).remove::<(CrowJob, CrowAssigned)>();
+            }
+        }
+    }
+    if !active || stealing || rng.0.f32() >= THEFT_RATE * time.delta_seconds() {
+        return;
+    }
+
+    let candidates: Vec<Entity> = placed
+        .iter()
+        .filter(|(_, anchors)| loosely_supported(anchors))
+        .map(|(entity, _)| entity)
+        .chain(
+            hanging
+                .iter()
+                // Decorations hang from delivering crows, not spawners
+                .filter(|(_, spawned_from)| spawners.contains(spawned_from.0))
+                .map(|(entity, _)| entity),
+        )
+        .filter(|entity| !jobs.contains(*entity))
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+    commands
+        .entity(candidates[rng.0.usize(0..candidates.len())])
+        .insert(CrowJob::new(CrowJobKind::Steal));
+}
+
+/// Reopens jobs whose crow has been despawned, scattered or given up on them.
+fn release_jobs(
+    mut commands: Commands,
+    jobs: Query<(Entity, &CrowAssigned)>,
+    crows: Query<(Option<&Employed>, Option<&Perched>), With<Crow>>,
+) {
+    for (job, CrowAssigned(crow)) in &jobs {
+        let working = match crows.get(*crow) {
+            Ok((Some(employed), _)) => employed.target() == Some(job),
+            Ok((None, Some(perched))) => perched.perch == job,
+            _ => false,
+        };
+        if !working {
+            commands.entity(job).remove::<CrowAssigned>();
+        }
+    }
+}
+
+/// Hands each open job, most important first, to the nearest idle crow. At most one roost is
+/// handed out per roll of `ROOST_RATE`.
+fn schedule_jobs(
+    mut commands: Commands,
+    jobs: Query<(Entity, &CrowJob, &GlobalTransform), Without<CrowAssigned>>,
+    crows: Query<
+        (Entity, &Transform),
+        (
+            With<Crow>,
+            Without<Employed>,
+            Without<Grab>,
+            Without<Perched>,
+            Without<Scattered>,
+        ),
+    >,
+    time: Res<Time>,
+) {
+    let mut roost_ready = fastrand::f32() < ROOST_RATE * time.delta_seconds();
+    let mut idle: Vec<_> = crows
+        .iter()
+        .map(|(entity, t)| (entity, t.translation.xy()))
+        .collect();
+    if idle.is_empty() {
+        return;
+    }
+    let mut open: Vec<_> = jobs.iter().collect();
+    open.sort_by_key(|(entity, job, _)| (-job.priority, *entity));
+
+    for (job_entity, job, transform) in open {
+        if let CrowJobKind::Roost { .. } = job.kind {
+            if !roost_ready {
+                continue;
+            }
+            roost_ready = false;
+        }
+        // Deliveries start by fetching a decoration from the exit
+        let start = match job.kind {
+            CrowJobKind::Deliver => EXIT,
+            _ => transform.translation().xy(),
+        };
+        let Some((i, _)) = idle
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.1.distance(start).total_cmp(&b.1.distance(start)))
+        else {
+            break;
+        };
+        let (crow, _) = idle.swap_remove(i);
+        let employment = match job.kind {
+            CrowJobKind::Pickup => Employed::ExitingToPickup(job_entity),
+            CrowJobKind::Deliver => Employed::ExitingToDeliver(job_entity, transform.translation()),
+            CrowJobKind::Harass => Employed::Harassing(job_entity),
+            CrowJobKind::Roost { block } => Employed::Roosting(job_entity, block),
+            CrowJobKind::Steal => Employed::Stealing(job_entity),
+        };
+        commands.entity(job_entity).insert(CrowAssigned(crow));
+        commands.entity(crow).insert(employment);
+    }
+}
+
+fn cancel_deliveries(mut commands: Commands, jobs: Query<(Entity, &CrowJob)>) {
+    for (entity, job) in &jobs {
+        if job.kind == CrowJobKind::Deliver {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
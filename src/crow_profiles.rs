@@ -0,0 +1,409 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{crow::CrowParams, environmental_decoration::TimeOfDay, weather::Weather};
+
+const PROFILES_PATH: &str = "crow_profiles.crows.json";
+
+/// Flocking parameters for one time of day, or for a storm. The flock picks a new `target` inside
+/// `target_min`..`target_max` about `retarget_rate` times a second, and scatters out to
+/// `scatter_distance` about `scatter_rate` times a second before drawing back in at
+/// `scatter_recovery` units a second.
+#[derive(Serialize, Deserialize, Reflect, Clone, Debug)]
+pub struct CrowProfile {
+    pub avoidance: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+    pub friction: f32,
+    pub avoid_distance: f32,
+    pub speed: f32,
+    pub max_speed: f32,
+    pub neighbor_radius: f32,
+    pub count: usize,
+    pub target_min: [f32; 2],
+    pub target_max: [f32; 2],
+    pub retarget_rate: f32,
+    pub scatter_distance: f32,
+    pub scatter_rate: f32,
+    pub scatter_recovery: f32,
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug)]
+pub struct CrowProfiles {
+    pub day: CrowProfile,
+    pub dusk: CrowProfile,
+    pub night: CrowProfile,
+    pub witching_hour: CrowProfile,
+    pub dawn: CrowProfile,
+    pub storm: CrowProfile,
+}
+
+/// Which of the profiles the flock follows: the storm's whenever one is raging, otherwise
+/// the time of day's.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CrowProfileKind {
+    TimeOfDay(TimeOfDay),
+    Storm,
+}
+
+impl CrowProfileKind {
+    fn label(&self) -> String {
+        match self {
+            CrowProfileKind::TimeOfDay(time_of_day) => format!("{time_of_day:?}"),
+            CrowProfileKind::Storm => "Storm".to_string(),
+        }
+    }
+}
+
+impl CrowProfiles {
+    pub fn get(&self, kind: CrowProfileKind) -> &CrowProfile {
+        match kind {
+            CrowProfileKind::TimeOfDay(TimeOfDay::Day) => &self.day,
+            CrowProfileKind::TimeOfDay(TimeOfDay::Dusk) => &self.dusk,
+            CrowProfileKind::TimeOfDay(TimeOfDay::Night) => &self.night,
+            CrowProfileKind::TimeOfDay(TimeOfDay::WitchingHour) => &self.witching_hour,
+            CrowProfileKind::TimeOfDay(TimeOfDay::Dawn) => &self.dawn,
+            CrowProfileKind::Storm => &self.storm,
+        }
+    }
+
+    fn get_mut(&mut self, kind: CrowProfileKind) -> &mut CrowProfile {
+        match kind {
+            CrowProfileKind::TimeOfDay(TimeOfDay::Day) => &mut self.day,
+            CrowProfileKind::TimeOfDay(TimeOfDay::Dusk) => &mut self.dusk,
+            CrowProfileKind::TimeOfDay(TimeOfDay::Night) => &mut self.night,
+            CrowProfileKind::TimeOfDay(TimeOfDay::WitchingHour) => &mut self.witching_hour,
+            CrowProfileKind::TimeOfDay(TimeOfDay::Dawn) => &mut self.dawn,
+            CrowProfileKind::Storm => &mut self.storm,
+        }
+    }
+}
+
+/// The profile the flock is currently following, `None` until the profiles have loaded.
+#[derive(Resource)]
+pub struct ActiveCrowProfile {
+    pub handle: Handle<CrowProfiles>,
+    pub kind: Option<CrowProfileKind>,
+}
+
+#[derive(Default)]
+struct CrowProfilesLoader;
+
+impl AssetLoader for CrowProfilesLoader {
+    type Asset = CrowProfiles;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["crows.json"]
+    }
+}
+
+#[derive(Component)]
+struct TuningPanel;
+
+#[derive(Component)]
+struct TuningValue(usize);
+
+#[derive(Component)]
+struct TuningButton(usize, f32);
+
+#[derive(Component)]
+struct TuningTitle;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Component)]
+struct SaveButton;
+
+type Getter = fn(&CrowProfile) -> f32;
+type Setter = fn(&mut CrowProfile, f32);
+
+/// Everything the tuning panel can edit: label, step per click, and accessors.
+const FIELDS: [(&str, f32, Getter, Setter); 13] = [
+    ("avoidance", 0.05, |p| p.avoidance, |p, v| p.avoidance = v),
+    ("alignment", 0.05, |p| p.alignment, |p, v| p.alignment = v),
+    ("cohesion", 0.05, |p| p.cohesion, |p, v| p.cohesion = v),
+    ("friction", 0.005, |p| p.friction, |p, v| p.friction = v),
+    (
+        "avoid distance",
+        0.5,
+        |p| p.avoid_distance,
+        |p, v| p.avoid_distance = v,
+    ),
+    ("speed", 0.01, |p| p.speed, |p, v| p.speed = v),
+    ("max speed", 0.05, |p| p.max_speed, |p, v| p.max_speed = v),
+    (
+        "neighbor radius",
+        1.0,
+        |p| p.neighbor_radius,
+        |p, v| p.neighbor_radius = v,
+    ),
+    (
+        "count",
+        10.0,
+        |p| p.count as f32,
+        |p, v| p.count = v.round() as usize,
+    ),
+    (
+        "retarget rate",
+        0.025,
+        |p| p.retarget_rate,
+        |p, v| p.retarget_rate = v,
+    ),
+    (
+        "scatter distance",
+        1.0,
+        |p| p.scatter_distance,
+        |p, v| p.scatter_distance = v,
+    ),
+    (
+        "scatter rate",
+        0.025,
+        |p| p.scatter_rate,
+        |p, v| p.scatter_rate = v,
+    ),
+    (
+        "scatter recovery",
+        0.5,
+        |p| p.scatter_recovery,
+        |p, v| p.scatter_recovery = v,
+    ),
+];
+
+pub struct CrowProfilesPlugin;
+
+impl Plugin for CrowProfilesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<CrowProfiles>()
+            .init_asset_loader::<CrowProfilesLoader>()
+            .register_type::<CrowProfile>()
+            .add_systems(Startup, load)
+            .add_systems(
+                Update,
+                (
+                    select_profile,
+                    toggle_tuning_panel,
+                    tuning_buttons,
+                    update_tuning_panel,
+                )
+                    .chain(),
+            );
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, save_button);
+    }
+}
+
+fn load(mut commands: Commands, assets: Res<AssetServer>) {
+    commands.insert_resource(ActiveCrowProfile {
+        handle: assets.load(PROFILES_PATH),
+        kind: None,
+    });
+}
+
+/// Follows the weather and the time of day. Copies the profile into `CrowParams` whenever it
+/// changes, including when the file is edited on disk.
+fn select_profile(
+    mut active: ResMut<ActiveCrowProfile>,
+    mut events: EventReader<AssetEvent<CrowProfiles>>,
+    profiles: Res<Assets<CrowProfiles>>,
+    mut params: ResMut<CrowParams>,
+    time_of_day: Res<TimeOfDay>,
+    weather: Res<Weather>,
+) {
+    let kind = if *weather == Weather::Storm {
+        CrowProfileKind::Storm
+    } else {
+        CrowProfileKind::TimeOfDay(*time_of_day)
+    };
+    let reloaded = events.read().any(|event| {
+        event.is_loaded_with_dependencies(&active.handle) || event.is_modified(&active.handle)
+    });
+    if !reloaded && active.kind == Some(kind) {
+        return;
+    }
+    let Some(profiles) = profiles.get(&active.handle) else {
+        return;
+    };
+    params.apply(profiles.get(kind), active.kind != Some(kind));
+    active.kind = Some(kind);
+}
+
+fn toggle_tuning_panel(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    panel: Query<Entity, With<TuningPanel>>,
+) {
+    if !keyboard.just_pressed(KeyCode::F2) {
+        return;
+    }
+    if let Ok(panel) = panel.get_single() {
+        commands.entity(panel).despawn_recursive();
+        return;
+    }
+
+    let text_style = TextStyle {
+        font_size: 16.0,
+        color: Color::srgb(0.9, 0.9, 0.9),
+        ..default()
+    };
+    let button = |parent: &mut ChildBuilder, label: &str, marker: TuningButton| {
+        parent
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(22.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: Color::srgb(0.2, 0.2, 0.2).into(),
+                    ..default()
+                },
+                marker,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+            });
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(16.0),
+                    bottom: Val::Px(16.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            TuningPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", text_style.clone()),
+                TuningTitle,
+            ));
+            for (i, (_, step, _, _)) in FIELDS.iter().enumerate() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(6.0),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        button(parent, "-", TuningButton(i, -step));
+                        button(parent, "+", TuningButton(i, *step));
+                        parent.spawn((
+                            TextBundle::from_section("", text_style.clone()),
+                            TuningValue(i),
+                        ));
+                    });
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                            ..default()
+                        },
+                        background_color: Color::srgb(0.2, 0.2, 0.2).into(),
+                        ..default()
+                    },
+                    SaveButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Save", text_style.clone()));
+                });
+        });
+}
+
+fn tuning_buttons(
+    interaction_query: Query<(&Interaction, &TuningButton), Changed<Interaction>>,
+    mut active: ResMut<ActiveCrowProfile>,
+    mut profiles: ResMut<Assets<CrowProfiles>>,
+) {
+    let Some(kind) = active.kind else {
+        return;
+    };
+    for (interaction, TuningButton(field, step)) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        // Editing the asset raises a modified event, which pushes the change into `CrowParams`
+        if let Some(profiles) = profiles.get_mut(&active.handle) {
+            let (_, _, get, set) = FIELDS[*field];
+            let profile = profiles.get_mut(kind);
+            set(profile, (get(profile) + step).max(0.0));
+        }
+        active.set_changed();
+    }
+}
+
+fn update_tuning_panel(
+    active: Res<ActiveCrowProfile>,
+    profiles: Res<Assets<CrowProfiles>>,
+    mut values: Query<(&mut Text, &TuningValue), Without<TuningTitle>>,
+    mut title: Query<&mut Text, With<TuningTitle>>,
+    added: Query<(), Added<TuningPanel>>,
+) {
+    if !active.is_changed() && added.is_empty() {
+        return;
+    }
+    let (Some(kind), Some(profiles)) = (active.kind, profiles.get(&active.handle)) else {
+        return;
+    };
+    let profile = profiles.get(kind);
+    for mut text in &mut title {
+        text.sections[0].value = format!("Crow profile: {} (F2 to close)", kind.label());
+    }
+    for (mut text, TuningValue(field)) in &mut values {
+        let (label, _, get, _) = FIELDS[*field];
+        text.sections[0].value = format!("{label}: {:.3}", get(profile));
+    }
+}
+
+/// Writes the tuned profiles back over the asset file so they survive a restart.
+#[cfg(not(target_arch = "wasm32"))]
+fn save_button(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<SaveButton>)>,
+    active: Res<ActiveCrowProfile>,
+    profiles: Res<Assets<CrowProfiles>>,
+) {
+    if !interaction_query.iter().any(|i| *i == Interaction::Pressed) {
+        return;
+    }
+    let Some(profiles) = profiles.get(&active.handle) else {
+        return;
+    };
+    let path = std::path::Path::new("assets").join(PROFILES_PATH);
+    match serde_json::to_string_pretty(profiles) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                log::warn!("Could not write {}: {e}", path.display());
+            }
+        }
+        Err(e) => log::warn!("Could not serialize crow profiles: {e}"),
+    }
+}
@@ -0,0 +1,441 @@
+use bevy::{prelude::*, render::primitives::Aabb, utils::HashMap};
+
+use crate::{
+    block::{Block, Collider},
+    build_phase::Foundation,
+    decay_phase::{DecayCause, Decayed, NeedsDecay, ReplacedBy},
+    environmental_decoration::Water,
+    water::Splash,
+    weather::Wind,
+    GameState,
+};
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct DebrisParams {
+    pub gravity: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    pub angular_damping: f32,
+    pub pieces_per_block: usize,
+    pub min_size: f32,
+    pub max_size: f32,
+    pub burst_speed: f32,
+    pub settle_speed: f32,
+    pub settle_steps: u32,
+    /// Damage dealt per unit of impact speed to intact blocks, `None` disables debris damage.
+    pub damage: Option<f32>,
+    pub seed: u64,
+}
+
+impl Default for DebrisParams {
+    fn default() -> Self {
+        Self {
+            gravity: 30.0,
+            restitution: 0.3,
+            friction: 0.6,
+            angular_damping: 0.5,
+            pieces_per_block: 6,
+            min_size: 0.2,
+            max_size: 0.6,
+            burst_speed: 4.0,
+            settle_speed: 0.1,
+            settle_steps: 16,
+            damage: Some(0.05),
+            seed: 0x5eed_cafe,
+        }
+    }
+}
+
+/// How quickly falling debris picks up the wind's speed.
+const WIND_DRAG: f32 = 0.5;
+
+#[derive(Component, Clone, Debug)]
+pub struct Debris {
+    pub velocity: Vec3,
+    pub angular_velocity: Vec3,
+    pub radius: f32,
+    pub resting_steps: u32,
+}
+
+#[derive(Component)]
+pub struct Settled;
+
+#[derive(Component, Default)]
+pub struct Damage(pub f32);
+
+#[derive(Component)]
+struct GroundCollider(Collider, Vec3, Quat);
+
+#[derive(Component)]
+struct ProcessedGround;
+
+#[derive(Resource)]
+struct DebrisRng(fastrand::Rng);
+
+#[derive(Resource)]
+struct DebrisAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct DebrisPlugin;
+
+impl Plugin for DebrisPlugin {
+    fn build(&self, app: &mut App) {
+        let params = DebrisParams::default();
+        app.register_type::<DebrisParams>()
+            .insert_resource(DebrisRng(fastrand::Rng::with_seed(params.seed)))
+            .insert_resource(params)
+            .add_systems(Startup, setup)
+            .add_systems(Update, (add_ground_colliders, spawn_debris, wake_debris))
+            .add_systems(FixedUpdate, (simulate_debris, apply_debris_damage).chain())
+            .add_systems(OnEnter(GameState::BuildPhase), reseed)
+            .add_systems(OnExit(GameState::ScoringPhase), cleanup);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(DebrisAssets {
+        mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::BLACK,
+            emissive: Color::srgb(0.15, 0.14, 0.16).into(),
+            ..default()
+        }),
+    });
+}
+
+fn reseed(mut rng: ResMut<DebrisRng>, params: Res<DebrisParams>) {
+    rng.0 = fastrand::Rng::with_seed(params.seed);
+}
+
+fn add_ground_colliders(
+    mut commands: Commands,
+    foundations: Query<Entity, With<Foundation>>,
+    children: Query<&Children>,
+    parents: Query<&Parent>,
+    meshes: Query<(&GlobalTransform, &Aabb), Without<ProcessedGround>>,
+    water: Query<Entity, With<Water>>,
+) {
+    for foundation in &foundations {
+        for entity in std::iter::once(foundation).chain(children.iter_descendants(foundation)) {
+            if water.contains(entity) || parents.iter_ancestors(entity).any(|e| water.contains(e)) {
+                continue;
+            }
+            if let Ok((transform, aabb)) = meshes.get(entity) {
+                let (scale, rotation, _) = transform.to_scale_rotation_translation();
+                let half_extents = (Vec3::from(aabb.half_extents) * scale).abs();
+                let offset = transform.transform_point(aabb.center.into()) - transform.translation();
+                commands.entity(entity).insert((
+                    ProcessedGround,
+                    GroundCollider(
+                        Collider(Box::new(parry3d::shape::Cuboid {
+                            half_extents: [half_extents.x, half_extents.y, half_extents.z].into(),
+                        })),
+                        offset,
+                        rotation,
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn spawn_debris(
+    mut commands: Commands,
+    query: Query<(&GlobalTransform, &Aabb), Or<(Added<Decayed>, Added<ReplacedBy>)>>,
+    params: Res<DebrisParams>,
+    assets: Res<DebrisAssets>,
+    mut rng: ResMut<DebrisRng>,
+) {
+    for (transform, aabb) in &query {
+        let center = transform.transform_point(aabb.center.into());
+        let (scale, _, _) = transform.to_scale_rotation_translation();
+        let half_extents = (Vec3::from(aabb.half_extents) * scale).abs();
+        for _ in 0..params.pieces_per_block {
+            let size = params.min_size + rng.0.f32() * (params.max_size - params.min_size);
+            let offset = Vec3::new(
+                (rng.0.f32() * 2.0 - 1.0) * half_extents.x,
+                (rng.0.f32() * 2.0 - 1.0) * half_extents.y,
+                0.0,
+            );
+            let direction = (offset.xy() + Vec2::new(0.0, half_extents.y)).normalize_or_zero();
+            let velocity = (direction * params.burst_speed * (0.5 + rng.0.f32())).extend(0.0);
+            let angular_velocity = Vec3::new(
+                rng.0.f32() * 2.0 - 1.0,
+                rng.0.f32() * 2.0 - 1.0,
+                rng.0.f32() * 2.0 - 1.0,
+            ) * 6.0;
+            let mut translation = center + offset;
+            translation.z += 0.5;
+            commands.spawn((
+                PbrBundle {
+                    mesh: assets.mesh.clone(),
+                    material: assets.material.clone(),
+                    transform: Transform::from_translation(translation)
+                        .with_scale(Vec3::splat(size)),
+                    ..default()
+                },
+                Debris {
+                    velocity,
+                    angular_velocity,
+                    radius: size * 0.5,
+                    resting_steps: 0,
+                },
+            ));
+        }
+    }
+}
+
+fn wake_debris(
+    mut commands: Commands,
+    changed: Query<(), Or<(Added<Decayed>, Added<ReplacedBy>)>>,
+    settled: Query<Entity, With<Settled>>,
+) {
+    if !changed.is_empty() {
+        for entity in &settled {
+            commands.entity(entity).remove::<Settled>();
+        }
+    }
+}
+
+/// Static geometry that debris can collide with, flattened out of the world for one step.
+pub struct DebrisObstacle<'a> {
+    pub entity: Option<Entity>,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub shape: &'a dyn parry3d::shape::Shape,
+}
+
+/// Advances one piece of debris by `dt` against `obstacles`. Returns the obstacle it hit
+/// hardest and the speed of that impact. Only depends on its inputs so that the simulation
+/// is reproducible under a fixed timestep.
+pub fn step_debris(
+    debris: &mut Debris,
+    transform: &mut Transform,
+    obstacles: &[DebrisObstacle],
+    params: &DebrisParams,
+    dt: f32,
+) -> Option<(Entity, f32)> {
+    debris.velocity.y -= params.gravity * dt;
+    transform.translation += debris.velocity * dt;
+    let angle = debris.angular_velocity.length() * dt;
+    if angle > 0.0 {
+        transform.rotate(Quat::from_axis_angle(debris.angular_velocity.normalize(), angle));
+    }
+
+    let ball = parry3d::shape::Ball::new(debris.radius);
+    let position = isometry(transform.translation, Quat::IDENTITY);
+    let mut hardest = None;
+    let mut touching = false;
+    for obstacle in obstacles {
+        let contact = parry3d::query::contact(
+            &position,
+            &ball,
+            &isometry(obstacle.translation, obstacle.rotation),
+            obstacle.shape,
+            0.0,
+        );
+        if let Ok(Some(contact)) = contact {
+            if contact.dist > 0.0 {
+                continue;
+            }
+            touching = true;
+            let normal = Vec3::new(contact.normal1.x, contact.normal1.y, contact.normal1.z);
+            transform.translation += normal * contact.dist;
+            let approach = debris.velocity.dot(normal);
+            if approach > 0.0 {
+                // A piece merely resting under its own weight shouldn't keep bouncing
+                let restitution = if approach > params.gravity * dt * 2.0 {
+                    params.restitution
+                } else {
+                    0.0
+                };
+                let tangent = debris.velocity - normal * approach;
+                debris.velocity = tangent * (1.0 - params.friction) - normal * approach * restitution;
+                debris.angular_velocity *= 1.0 - params.angular_damping;
+                if let Some(entity) = obstacle.entity {
+                    if hardest.map(|(_, s)| approach > s).unwrap_or(true) {
+                        hardest = Some((entity, approach));
+                    }
+                }
+            }
+        }
+    }
+
+    if touching && debris.velocity.length() < params.settle_speed {
+        debris.resting_steps += 1;
+    } else {
+        debris.resting_steps = 0;
+    }
+    hardest
+}
+
+fn isometry(translation: Vec3, rotation: Quat) -> parry3d::math::Isometry<f32> {
+    parry3d::math::Isometry::from_parts(
+        [translation.x, translation.y, translation.z].into(),
+        parry3d::na::UnitQuaternion::new_normalize(parry3d::na::Quaternion::new(
+            rotation.w, rotation.x, rotation.y, rotation.z,
+        )),
+    )
+}
+
+fn simulate_debris(
+    mut commands: Commands,
+    mut debris: Query<(Entity, &mut Debris, &mut Transform), Without<Settled>>,
+    blocks: Query<
+        (Entity, &GlobalTransform, &Collider),
+        (With<Block>, Without<Decayed>, Without<NeedsDecay>),
+    >,
+    ground: Query<(&GlobalTransform, &GroundCollider)>,
+    water: Query<&GlobalTransform, With<Water>>,
+    params: Res<DebrisParams>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+    mut splashes: EventWriter<Splash>,
+    mut damage: Query<&mut Damage>,
+) {
+    let dt = time.delta_seconds();
+    let mut obstacles: Vec<DebrisObstacle> = blocks
+        .iter()
+        .map(|(entity, transform, collider)| DebrisObstacle {
+            entity: Some(entity),
+            translation: transform.translation(),
+            rotation: transform.to_scale_rotation_translation().1,
+            shape: collider.0.as_ref(),
+        })
+        .collect();
+    obstacles.extend(ground.iter().map(|(transform, ground)| DebrisObstacle {
+        entity: None,
+        translation: transform.translation() + ground.1,
+        rotation: ground.2,
+        shape: ground.0 .0.as_ref(),
+    }));
+    let floor = water
+        .iter()
+        .map(|t| t.translation().y)
+        .fold(-50.0, f32::max);
+
+    // Several pieces can strike the same block in one step, so their impacts are summed first
+    let mut impacts: HashMap<Entity, f32> = HashMap::new();
+    for (entity, mut piece, mut transform) in &mut debris {
+        piece.velocity.x += wind.0 * WIND_DRAG * dt;
+        if let Some((hit, speed)) = step_debris(&mut piece, &mut transform, &obstacles, &params, dt) {
+            *impacts.entry(hit).or_default() += speed;
+        }
+        if transform.translation.y < floor {
+            splashes.send(Splash {
+                position: transform.translation.with_y(floor),
+                strength: (piece.radius * 2.0).min(1.0),
+            });
+            commands.entity(entity).despawn_recursive();
+        } else if piece.resting_steps >= params.settle_steps {
+            piece.velocity = Vec3::ZERO;
+            piece.angular_velocity = Vec3::ZERO;
+            commands.entity(entity).insert(Settled);
+        }
+    }
+
+    if let Some(scale) = params.damage {
+        let scale = scale * wind.collapse();
+        for (entity, speed) in impacts {
+            if let Ok(mut damage) = damage.get_mut(entity) {
+                damage.0 += speed * scale;
+            } else {
+                commands.entity(entity).insert(Damage(speed * scale));
+            }
+        }
+    }
+}
+
+fn apply_debris_damage(
+    mut commands: Commands,
+    query: Query<(Entity, &Damage), (Changed<Damage>, With<Block>, Without<Decayed>)>,
+) {
+    for (entity, damage) in &query {
+        if damage.0 >= 1.0 {
+            commands
+                .entity(entity)
+                .remove::<Damage>()
+                .insert((NeedsDecay, DecayCause::Debris));
+        }
+    }
+}
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<Debris>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 64.0;
+
+    /// Drops a seeded handful of pieces onto a tilted block standing on the ground, returning
+    /// where each came to rest and the total impact speed dealt to the block.
+    fn drop_pieces(seed: u64) -> (Vec<Transform>, f32) {
+        let params = DebrisParams::default();
+        let block = Entity::from_raw(1);
+        let block_shape = parry3d::shape::Cuboid::new([1.0, 1.0, 1.0].into());
+        let ground_shape = parry3d::shape::Cuboid::new([20.0, 1.0, 20.0].into());
+        let obstacles = [
+            DebrisObstacle {
+                entity: Some(block),
+                translation: Vec3::new(0.0, 1.0, 0.0),
+                rotation: Quat::from_rotation_z(0.3),
+                shape: &block_shape,
+            },
+            DebrisObstacle {
+                entity: None,
+                translation: Vec3::new(0.0, -1.0, 0.0),
+                rotation: Quat::IDENTITY,
+                shape: &ground_shape,
+            },
+        ];
+
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let mut rested = vec![];
+        let mut damage = 0.0;
+        for _ in 0..8 {
+            let mut debris = Debris {
+                velocity: Vec3::new(rng.f32() * 2.0 - 1.0, 0.0, 0.0),
+                angular_velocity: Vec3::new(rng.f32(), rng.f32(), rng.f32()) * 6.0,
+                radius: 0.1 + rng.f32() * 0.2,
+                resting_steps: 0,
+            };
+            let mut transform = Transform::from_xyz(rng.f32() - 0.5, 6.0, 0.0);
+            let mut steps = 0;
+            while debris.resting_steps < params.settle_steps {
+                if let Some((hit, speed)) =
+                    step_debris(&mut debris, &mut transform, &obstacles, &params, DT)
+                {
+                    assert_eq!(hit, block);
+                    damage += speed;
+                }
+                steps += 1;
+                assert!(steps < 64 * 30, "debris never came to rest");
+            }
+            assert!(transform.translation.y > -0.5, "debris fell through the ground");
+            rested.push(transform);
+        }
+        (rested, damage)
+    }
+
+    #[test]
+    fn debris_settles_damages_and_repeats() {
+        let (rested, damage) = drop_pieces(0x5eed);
+        assert!(damage > 0.0);
+
+        let (rested_again, damage_again) = drop_pieces(0x5eed);
+        assert_eq!(rested, rested_again);
+        assert_eq!(damage, damage_again);
+    }
+}
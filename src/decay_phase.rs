@@ -3,17 +3,27 @@ use bevy::{
     reflect::TypePath,
     render::render_resource::{AsBindGroup, ShaderRef},
 };
-use std::time::Duration;
+use std::{collections::HashSet, time::Duration};
 
 use bevy_kira_audio::prelude::*;
-use blenvy::{BlueprintAnimationPlayerLink, BlueprintAnimations};
+use bevy_mod_picking::prelude::*;
+use blenvy::{
+    BlueprintAnimationPlayerLink, BlueprintAnimations, BlueprintInfo, BlueprintInstanceReady,
+    GameWorldTag, HideUntilReady, SpawnBlueprint,
+};
 
 use crate::{
-    block::{WeirdMachine, AnchorState, Anchors, Block, Conductor, DecayedRepresentation, DisasterTarget},
+    achievements::{DisasterResolved, LightningChain},
+    block::{
+        WeirdMachine, AnchorColor, AnchorState, Anchors, Block, BlockScoring, BlockTags, Conductor,
+        DecayedRepresentation, DisasterTarget,
+    },
     environmental_decoration::{Sky, Star},
-    music::{BackgroundMusic, Music},
-    block_pool::BlockPoolResident,
-    CameraScale, GameState, MousePos, SNAP_DISTANCE,
+    music::{EffectsChannel, Music},
+    scoring_phase::Scored,
+    villagers::{Fleeing, Villager},
+    weather::{Weather, Wind},
+    CameraScale, GameState, MousePos, RoundRng, SpawnedFrom, WorldClicks, SNAP_DISTANCE,
 };
 
 #[derive(Component, Reflect)]
@@ -22,6 +32,25 @@ pub struct NeedsDecay;
 
 #[derive(Component)]
 pub struct Decayed;
+
+#[derive(Component, Reflect, Copy, Clone, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum DecayCause {
+    Lightning,
+    Debris,
+    /// Toppled by the wind when the ruin it stood on gave way.
+    Wind,
+}
+
+#[derive(Component)]
+struct DecayedReplacement;
+
+/// A ruin spawned from a block's `DecayedRepresentation` rather than desaturated in place.
+#[derive(Component)]
+pub struct RuinedVariant;
+
+#[derive(Component)]
+pub struct ReplacedBy(pub Entity);
 #[derive(Component, Reflect, Copy, Clone, Debug)]
 #[reflect(Component)]
 pub struct Eye;
@@ -64,8 +93,9 @@ pub struct SkyTentacle;
 #[derive(Component)]
 struct ActiveTentacle;
 
+/// A strike being aimed, with the conductors it will arc through.
 #[derive(Component)]
-struct Lightning(Vec<Entity>);
+pub struct Lightning(Vec<Entity>);
 
 pub struct DecayPhasePlugin;
 
@@ -76,6 +106,7 @@ impl Plugin for DecayPhasePlugin {
             .register_type::<Disaster>()
             .register_type::<Eye>()
             .register_type::<DecayedRepresentation>()
+            .register_type::<DecayCause>()
             .register_type::<SkyTentacle>()
             .register_type::<DarkFigureBody>()
             .insert_state(PhasePhase::Running)
@@ -83,12 +114,21 @@ impl Plugin for DecayPhasePlugin {
                 PostUpdate,
                 hide_dark_figure.run_if(not(in_state(crate::GameState::DecayPhase))),
             )
-            .add_systems(Update, (screen_flash, apply_decay))
+            .add_systems(FixedUpdate, screen_flash)
+            .add_systems(Startup, setup_bolt_mesh)
+            .add_systems(
+                Update,
+                (
+                    apply_decay,
+                    finish_decay_replacement,
+                    strip_decayed_anchors,
+                    flash_screen,
+                ),
+            )
             .add_systems(
                 OnEnter(crate::GameState::DecayPhase),
                 (
                     setup_phase,
-                    start_decay_loop,
                     |mut next_state: ResMut<NextState<PhasePhase>>| {
                         next_state.set(PhasePhase::Running)
                     },
@@ -150,31 +190,11 @@ fn spawn_dark_figure(
     }
 }
 
-fn start_decay_loop(
-    mut background: ResMut<BackgroundMusic>,
-    music: Res<Music>,
-    audio: Res<Audio>,
-    mut instances: ResMut<Assets<AudioInstance>>,
-) {
-    if let Some(player) = background.1.as_ref().and_then(|h| instances.get_mut(h)) {
-        player.stop(AudioTween::linear(Duration::from_secs(1)));
-    }
-    if let Some(cur) = audio.state(&background.0).position() {
-        let handle = audio
-            .play(music.decay_overlay.clone())
-            .start_from(cur)
-            .fade_in(AudioTween::linear(Duration::from_secs(2)))
-            .looped()
-            .handle();
-        background.1 = Some(handle);
-    }
-}
-
 fn dark_figure_animation_control(
     animations: Query<(&BlueprintAnimationPlayerLink, &BlueprintAnimations), With<DarkFigureBody>>,
     mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
     music: Res<crate::music::Music>,
-    audio: Res<Audio>,
+    effects: Res<AudioChannel<EffectsChannel>>,
 ) {
     for (link, animations) in animations.iter() {
         let (mut animation_player, mut transition) = animation_players.get_mut(link.0).unwrap();
@@ -187,7 +207,7 @@ fn dark_figure_animation_control(
                             *emerge_animation,
                             std::time::Duration::ZERO,
                         );
-                        audio.play(music.dark_figure_hit.clone());
+                        effects.play(music.dark_figure_hit.clone());
                     } else if animation_player.all_finished() {
                         transition
                             .play(
@@ -239,21 +259,118 @@ fn maintain_active_tentacle(
     }
 }
 
+const LINE_POINTS: usize = 16;
+const CONDUCTION_DISTANCE: f32 = 5.0;
+const CONDUCTION_BUDGET: f32 = 15.0;
+const FORK_FALLOFF: f32 = 0.5;
+const BOLT_MARGIN: f32 = 2.0;
+
+type BoltPoint = (Entity, Vec3, f32);
+
+fn trace_bolt(
+    start: BoltPoint,
+    budget: f32,
+    reach: f32,
+    conductors: &[(Entity, Vec3, Quat, bool)],
+    struck: &mut HashSet<Entity>,
+) -> Vec<BoltPoint> {
+    let mut path = vec![start];
+    let mut total_travel = budget;
+    let mut did_work = true;
+    while did_work && total_travel > 0.0 {
+        did_work = false;
+        let mut min_dist = std::f32::INFINITY;
+        let mut closest = None;
+        for (entity, translation, rotation, is_machine) in conductors {
+            if struck.contains(entity) {
+                continue;
+            }
+            let d = (path[path.len() - 1].1 - *translation).length();
+            if d < reach && d < min_dist {
+                min_dist = d;
+                closest = Some((*entity, *translation, *rotation, *is_machine));
+            }
+        }
+        if let Some((entity, translation, rotation, is_machine)) = closest {
+            did_work = true;
+            struck.insert(entity);
+            path.push((entity, translation, total_travel / CONDUCTION_BUDGET));
+            if is_machine {
+                total_travel += CONDUCTION_BUDGET;
+                path.push((
+                    entity,
+                    translation + rotation.mul_vec3(Vec3::new(5.0, 0.0, 0.0)),
+                    total_travel / CONDUCTION_BUDGET,
+                ));
+            } else {
+                total_travel -= min_dist;
+            }
+        }
+    }
+    path
+}
+
+/// A unit square every bolt chunk stretches over the points it draws. The shader works in
+/// world space so the stretching doesn't distort the bolt.
+#[derive(Resource)]
+struct BoltMesh(Handle<Mesh>);
+
+/// One piece of a bolt, drawing up to `LINE_POINTS` of it.
+#[derive(Component)]
+struct BoltChunk;
+
+fn setup_bolt_mesh(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(BoltMesh(meshes.add(Rectangle::new(1.0, 1.0))));
+}
+
+/// Splits `bolts` into pieces of at most `LINE_POINTS` points, each with the transform that
+/// covers it.
+fn bolt_chunks(bolts: &[Vec<Vec4>]) -> Vec<(LineMaterial, Transform)> {
+    let mut chunks = vec![];
+    for bolt in bolts {
+        // Consecutive chunks share an end point so the bolt stays continuous
+        let mut start = 0;
+        while start + 1 < bolt.len() {
+            let chunk = &bolt[start..(start + LINE_POINTS).min(bolt.len())];
+            let mut material = LineMaterial::default();
+            let mut min = Vec2::splat(std::f32::INFINITY);
+            let mut max = Vec2::splat(-std::f32::INFINITY);
+            for (i, point) in chunk.iter().enumerate() {
+                material.points[i] = *point;
+                min = min.min(point.xy());
+                max = max.max(point.xy());
+            }
+            material.point_count = chunk.len() as u32;
+            let size = max - min + Vec2::splat(BOLT_MARGIN * 2.0);
+            chunks.push((
+                material,
+                Transform::from_translation(((min + max) / 2.0).extend(0.0))
+                    .with_scale(size.extend(1.0)),
+            ));
+            start += LINE_POINTS - 1;
+        }
+    }
+    chunks
+}
+
 fn targeting(
     mut commands: Commands,
     mouse_pos: Res<MousePos>,
     targets: Query<(Entity, &GlobalTransform, &DisasterTarget)>,
     conductors: Query<(Entity, &GlobalTransform, Option<&WeirdMachine>), With<Conductor>>,
-    mut strikes: Query<(Entity, &mut Lightning)>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut strikes: Query<(Entity, &mut Lightning, Option<&Children>)>,
+    mut chunks: Query<(&mut Transform, &Handle<LineMaterial>), With<BoltChunk>>,
+    bolt_mesh: Res<BoltMesh>,
     mut materials: ResMut<Assets<LineMaterial>>,
     tentacles: Query<&GlobalTransform, With<ActiveTentacle>>,
     camera_scale: Res<CameraScale>,
-    audio: Res<Audio>,
+    weather: Res<Weather>,
+    effects: Res<AudioChannel<EffectsChannel>>,
     music: Res<Music>,
     mut spark: ResMut<SparkSound>,
     mut instances: ResMut<Assets<AudioInstance>>,
 ) {
+    let reach = CONDUCTION_DISTANCE * weather.conduction();
     if let Some(tentacle_transform) = tentacles.iter().next() {
         let mut snapped = None;
 
@@ -277,97 +394,100 @@ fn targeting(
 
         if let Some(snapped) = snapped {
             maybe_pos.z = 0.0;
-            let mut found = false;
-            let mut material = LineMaterial {
-                point_count: 1,
-                ..default()
-            };
-            let mut targets = vec![(snapped, maybe_pos.clone(), 1.0)];
-            let mut total_travel = 15.0;
-            let mut did_work = true;
-            while did_work && total_travel > 0.0 {
-                did_work = false;
-                let mut min_dist = std::f32::INFINITY;
-                let mut closest = None;
-                for (entity, transform, maybe_machine) in &conductors {
-                    if targets.iter().any(|(e, _, _)| *e == entity) {
-                        continue;
-                    }
-                    let d = (targets[targets.len() - 1].1 - transform.translation()).length();
-                    if d < 5.0 && d < min_dist {
-                        min_dist = d;
-                        let (_, rotation, translation) = transform.to_scale_rotation_translation();
-                        closest = Some((entity, translation, rotation, maybe_machine.is_some()));
-                    }
-                }
-                if let Some((entity, translation, rotation, is_machine)) = closest {
-                    did_work = true;
-                    targets.push((entity, translation, total_travel/15.0));
-                    if is_machine {
-                        total_travel += 15.0;
-                        targets.push((entity, translation + rotation.mul_vec3(Vec3::new(5.0, 0.0, 0.0)), total_travel/15.0));
-                    } else {
-                        total_travel -= min_dist;
+            let conductors: Vec<_> = conductors
+                .iter()
+                .map(|(entity, transform, maybe_machine)| {
+                    let (_, rotation, translation) = transform.to_scale_rotation_translation();
+                    (entity, translation, rotation, maybe_machine.is_some())
+                })
+                .collect();
+            let mut struck = HashSet::from([snapped]);
+            let main = trace_bolt(
+                (snapped, maybe_pos, 1.0),
+                CONDUCTION_BUDGET,
+                reach,
+                &conductors,
+                &mut struck,
+            );
+
+            let mut bolts = vec![std::iter::once(tentacle_transform.translation().extend(1.0))
+                .chain(main.iter().map(|(_, p, w)| p.extend(*w)))
+                .collect::<Vec<_>>()];
+            let mut fork_points = main.clone();
+            while let Some(fork_point) = fork_points.pop() {
+                loop {
+                    let branch = trace_bolt(
+                        fork_point,
+                        fork_point.2 * CONDUCTION_BUDGET * FORK_FALLOFF,
+                        reach,
+                        &conductors,
+                        &mut struck,
+                    );
+                    if branch.len() < 2 {
+                        break;
                     }
+                    bolts.push(branch.iter().map(|(_, p, w)| p.extend(*w)).collect());
+                    fork_points.extend(branch.into_iter().skip(1));
                 }
             }
-            material.points[0] = tentacle_transform.translation().extend(1.0);
-            for i in 0..14.min(targets.len()) {
-                material.points[i + 1] = targets[i].1.extend(targets[i].2);
-                material.point_count += 1;
-            }
-            let mut targets: Vec<Entity> = targets.into_iter().map(|(e, _, _)| e).collect();
-            targets.insert(0, snapped);
-            for (entity, mut lightning) in &mut strikes {
-                lightning.0 = targets.clone();
-                commands.entity(entity).insert((MaterialMeshBundle {
-                    mesh: meshes.add(Rectangle {
-                        half_size: Vec2::new(100.0, 100.0),
-                    }),
-                    material: materials.add(material.clone()),
-                    ..default()
-                },));
-                if spark.0.is_none() {
-                    spark.0 = Some(
-                        audio
-                            .play(music.spark.clone())
-                            .start_from(1.2)
-                            .linear_fade_in(Duration::from_millis(250))
-                            .with_volume(0.125)
-                            .loop_from(1.2)
-                            .loop_until(9.0)
-                            .handle(),
-                    );
-                }
-                found = true;
-                break;
+
+            let mut targets = vec![snapped];
+            targets.extend(struck.into_iter().filter(|e| *e != snapped));
+
+            // Reuse the chunks already drawn, only adding or removing any the bolt has
+            // gained or lost since
+            let (strike, existing) =
+                if let Some((entity, mut lightning, children)) = strikes.iter_mut().next() {
+                    lightning.0 = targets;
+                    let existing = children.map(|c| c.to_vec()).unwrap_or_default();
+                    (entity, existing)
+                } else {
+                    let entity = commands
+                        .spawn((SpatialBundle::default(), Lightning(targets)))
+                        .id();
+                    (entity, vec![])
+                };
+            let new_chunks = bolt_chunks(&bolts);
+            for &entity in existing.iter().skip(new_chunks.len()) {
+                commands.entity(entity).despawn_recursive();
             }
-            if !found {
-                commands.spawn((
-                    MaterialMeshBundle {
-                        mesh: meshes.add(Rectangle {
-                            half_size: Vec2::new(100.0, 100.0),
-                        }),
-                        material: materials.add(material),
-                        ..default()
-                    },
-                    Lightning(targets),
-                ));
-                if spark.0.is_none() {
-                    spark.0 = Some(
-                        audio
-                            .play(music.spark.clone())
-                            .start_from(1.2)
-                            .linear_fade_in(Duration::from_millis(250))
-                            .with_volume(0.125)
-                            .loop_from(1.2)
-                            .loop_until(9.0)
-                            .handle(),
-                    );
+            commands.entity(strike).with_children(|parent| {
+                for (i, (material, transform)) in new_chunks.into_iter().enumerate() {
+                    let reused = existing
+                        .get(i)
+                        .and_then(|entity| chunks.get_mut(*entity).ok());
+                    if let Some((mut chunk_transform, handle)) = reused {
+                        *chunk_transform = transform;
+                        if let Some(chunk_material) = materials.get_mut(handle) {
+                            *chunk_material = material;
+                        }
+                        continue;
+                    }
+                    parent.spawn((
+                        MaterialMeshBundle {
+                            mesh: bolt_mesh.0.clone(),
+                            material: materials.add(material),
+                            transform,
+                            ..default()
+                        },
+                        BoltChunk,
+                    ));
                 }
+            });
+            if spark.0.is_none() {
+                spark.0 = Some(
+                    effects
+                        .play(music.spark.clone())
+                        .start_from(1.2)
+                        .linear_fade_in(Duration::from_millis(250))
+                        .with_volume(0.125)
+                        .loop_from(1.2)
+                        .loop_until(9.0)
+                        .handle(),
+                );
             }
         } else {
-            for (entity, _transform) in &strikes {
+            for (entity, _, _) in &strikes {
                 commands.entity(entity).despawn_recursive();
             }
             if let Some(player) = spark.0.take().and_then(|h| instances.get_mut(&h)) {
@@ -382,39 +502,30 @@ fn activate_disaster(
     query: Query<(Entity, &Lightning)>,
     blocks: Query<Entity, (With<Block>, Without<NeedsDecay>, Without<Decayed>)>,
     parents: Query<&Parent>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    clicks: WorldClicks,
     mut anchors: Query<&mut Anchors>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
     tentacles: Query<(Entity, &GlobalTransform), With<ActiveTentacle>>,
-    audio: Res<Audio>,
+    effects: Res<AudioChannel<EffectsChannel>>,
     music: Res<Music>,
     mut spark: ResMut<SparkSound>,
     mut instances: ResMut<Assets<AudioInstance>>,
+    machines: Query<(), With<WeirdMachine>>,
+    mut chains: EventWriter<LightningChain>,
+    mut resolved: EventWriter<DisasterResolved>,
 ) {
     if let Some((tentacle_entity, _tentacle_transform)) = tentacles.iter().next() {
-        if mouse_button_input.just_released(MouseButton::Left) {
+        if clicks.just_released() {
             commands.entity(tentacle_entity).remove::<ActiveTentacle>();
-            audio.play(music.thunder.clone());
+            effects.play(music.thunder.clone());
             if let Some(player) = spark.0.take().and_then(|h| instances.get_mut(&h)) {
                 player.stop(AudioTween::linear(Duration::from_millis(250)));
             }
-            commands
-                .spawn(PbrBundle {
-                    mesh: meshes.add(Rectangle {
-                        half_size: Vec2::new(100.0, 100.0),
-                    }),
-                    material: materials.add(StandardMaterial {
-                        base_color: Color::srgba(0.0, 0.0, 0.0, 1.0),
-                        emissive: Color::srgb(1000.0, 1000.0, 1000.0).into(),
-                        alpha_mode: AlphaMode::Blend,
-                        ..default()
-                    }),
-                    ..default()
-                })
-                .insert(ScreenFlash(default(), std::time::Duration::from_millis(75)));
-            let mut done = std::collections::HashSet::new();
+            let mut done = HashSet::new();
             for (entity, lightning) in &query {
+                chains.send(LightningChain {
+                    length: lightning.0.len(),
+                    machines: lightning.0.iter().filter(|e| machines.contains(**e)).count(),
+                });
                 for targeted_entity in &lightning.0 {
                     for ancestor in std::iter::once(*targeted_entity)
                         .chain(parents.iter_ancestors(*targeted_entity))
@@ -429,7 +540,7 @@ fn activate_disaster(
                                     if blocks.contains(entity) {
                                         commands
                                             .entity(entity)
-                                            .insert(NeedsDecay);
+                                            .insert((NeedsDecay, DecayCause::Lightning));
 
                                         done.insert(entity);
                                     }
@@ -442,7 +553,7 @@ fn activate_disaster(
                         if blocks.contains(ancestor) {
                             commands
                                 .entity(ancestor)
-                                .insert(NeedsDecay);
+                                .insert((NeedsDecay, DecayCause::Lightning));
                             done.insert(ancestor);
                             break;
                         }
@@ -450,18 +561,109 @@ fn activate_disaster(
                 }
                 commands.entity(entity).despawn_recursive();
             }
+            resolved.send(DisasterResolved {
+                decayed: done.len(),
+            });
         }
     }
 }
 
 fn apply_decay(
     mut commands: Commands,
-    query: Query<Entity, With<NeedsDecay>>,
+    query: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&DecayedRepresentation>,
+            Option<&Parent>,
+            Option<&SpawnedFrom>,
+            Option<&Scored>,
+            Option<&DecayCause>,
+            Option<&BlockTags>,
+            Option<&BlockScoring>,
+        ),
+        With<NeedsDecay>,
+    >,
     children: Query<&Children>,
+    mut anchors: Query<(Entity, &mut Anchors)>,
+    intact: Query<(), (With<Block>, Without<NeedsDecay>, Without<Decayed>)>,
     mut material_handle: Query<&mut Handle<StandardMaterial>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    wind: Res<Wind>,
+    mut rng: ResMut<RoundRng>,
 ) {
-    for needy_entity in &query {
+    for (needy_entity, transform, representation, parent, spawned_from, scored, cause, tags, scoring) in
+        &query
+    {
+        // Whatever stood on the ruin may come down with it, the harder the wind the likelier
+        if let Ok((_, needy_anchors)) = anchors.get(needy_entity) {
+            for (_, color, state, _) in &needy_anchors.0 {
+                if let (AnchorColor::Up, AnchorState::Occupied(above)) = (color, state) {
+                    if intact.contains(*above) && rng.0.f32() < wind.topple_chance() {
+                        commands.entity(*above).insert((NeedsDecay, DecayCause::Wind));
+                    }
+                }
+            }
+        }
+        if let Some(representation) = representation {
+            let mut replacement = commands.spawn((
+                *transform,
+                BlueprintInfo::from_path(&representation.path()),
+                SpawnBlueprint,
+                HideUntilReady,
+                GameWorldTag,
+                Block,
+                Decayed,
+                DecayedReplacement,
+                RuinedVariant,
+            ));
+            if let Some(parent) = parent {
+                replacement.set_parent(parent.get());
+            }
+            if let Some(spawned_from) = spawned_from {
+                replacement.insert(SpawnedFrom(spawned_from.0));
+            }
+            if scored.is_some() {
+                replacement.insert(Scored);
+            }
+            if let Some(cause) = cause {
+                replacement.insert(*cause);
+            }
+            if let Some(tags) = tags {
+                replacement.insert(tags.clone());
+            }
+            if let Some(scoring) = scoring {
+                replacement.insert(scoring.clone());
+            }
+            let replacement = replacement.id();
+
+            for (anchor_entity, mut anchors) in &mut anchors {
+                if anchor_entity == needy_entity {
+                    continue;
+                }
+                for (_, _, anchor_state, _) in &mut anchors.0 {
+                    match anchor_state {
+                        AnchorState::Occupied(e) | AnchorState::Blocked(e) if *e == needy_entity => {
+                            *e = replacement;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+
+            // The original stays visible until its ruined variant has finished loading
+            commands
+                .entity(needy_entity)
+                .remove::<NeedsDecay>()
+                .remove::<Anchors>()
+                .remove::<Block>()
+                .insert(ReplacedBy(replacement));
+            for entity in std::iter::once(needy_entity).chain(children.iter_descendants(needy_entity)) {
+                commands.entity(entity).remove::<DisasterTarget>().remove::<Conductor>();
+            }
+            continue;
+        }
+
         commands.entity(needy_entity).remove::<NeedsDecay>().remove::<Anchors>().insert(Decayed);
         for entity in std::iter::once(needy_entity).chain(children.iter_descendants(needy_entity)) {
             commands.entity(entity).remove::<DisasterTarget>().remove::<Conductor>();
@@ -473,13 +675,42 @@ fn apply_decay(
     }
 }
 
+fn finish_decay_replacement(
+    mut commands: Commands,
+    replacements: Query<Entity, (With<DecayedReplacement>, Added<BlueprintInstanceReady>)>,
+    replaced: Query<(Entity, &ReplacedBy)>,
+    children: Query<&Children>,
+) {
+    for replacement in &replacements {
+        commands.entity(replacement).remove::<DecayedReplacement>();
+        for entity in std::iter::once(replacement).chain(children.iter_descendants(replacement)) {
+            commands
+                .entity(entity)
+                .remove::<DisasterTarget>()
+                .remove::<Conductor>()
+                .insert(Pickable::IGNORE);
+        }
+        for (entity, replaced_by) in &replaced {
+            if replaced_by.0 == replacement {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn strip_decayed_anchors(mut commands: Commands, query: Query<Entity, (With<Decayed>, With<Anchors>)>) {
+    for entity in &query {
+        commands.entity(entity).remove::<Anchors>();
+    }
+}
+
 fn check_completion(
     mut next_state: ResMut<NextState<GameState>>,
     mut next_local_state: ResMut<NextState<PhasePhase>>,
     tentacles: Query<Entity, Or<(With<ActiveTentacle>, With<Disaster>)>>,
-    blocks: Query<Entity, (With<Block>, Without<Decayed>, Without<BlockPoolResident>)>,
+    villagers: Query<Entity, (With<Villager>, Without<Fleeing>)>,
 ) {
-    if tentacles.is_empty() || blocks.is_empty() {
+    if tentacles.is_empty() || villagers.is_empty() {
         next_state.set(GameState::ScoringPhase);
         next_local_state.set(PhasePhase::Idle);
     }
@@ -488,7 +719,7 @@ fn check_completion(
 #[derive(Asset, TypePath, Default, AsBindGroup, Debug, Clone)]
 struct LineMaterial {
     #[uniform(100)]
-    points: [Vec4; 16],
+    points: [Vec4; LINE_POINTS],
     #[uniform(100)]
     point_count: u32,
 }
@@ -503,6 +734,39 @@ impl Material for LineMaterial {
     }
 }
 
+/// Whites out what the camera sees for a moment whenever a strike lands.
+fn flash_screen(
+    mut commands: Commands,
+    mut resolved: EventReader<DisasterResolved>,
+    cameras: Query<(&GlobalTransform, &Projection), With<Camera3d>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if resolved.read().count() == 0 {
+        return;
+    }
+    for (transform, projection) in &cameras {
+        let Projection::Orthographic(projection) = projection else {
+            continue;
+        };
+        // A little extra so the camera can't slide an edge into view while it lasts
+        let half_size = projection.area.half_size() + Vec2::splat(BOLT_MARGIN);
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Rectangle { half_size }),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::srgba(0.0, 0.0, 0.0, 1.0),
+                    emissive: Color::srgb(1000.0, 1000.0, 1000.0).into(),
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                }),
+                transform: Transform::from_translation(transform.translation().xy().extend(0.0)),
+                ..default()
+            })
+            .insert(ScreenFlash(default(), std::time::Duration::from_millis(75)));
+    }
+}
+
 fn screen_flash(
     mut commands: Commands,
     mut query: Query<(Entity, &mut ScreenFlash, &Handle<StandardMaterial>)>,
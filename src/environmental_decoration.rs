@@ -1,21 +1,14 @@
 use std::time::Duration;
 
-use bevy::{
-    prelude::*,
-    time::common_conditions::on_timer,
-};
+use bevy::prelude::*;
 use blenvy::*;
 
-use crate::CLOUDS;
+use crate::{levels::CurrentLevel, GameState};
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct FoundationIdle;
 
-#[derive(Component, Reflect)]
-#[reflect(Component)]
-pub struct Cloud;
-
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct Star;
@@ -24,11 +17,63 @@ pub struct Star;
 #[reflect(Component)]
 pub struct Water;
 
+/// Where the round is in the night. Building happens at dusk, ruining at midnight and
+/// scoring at dawn.
 #[derive(Resource, PartialEq, Eq, Debug, Default, Copy, Clone)]
 pub enum TimeOfDay {
     #[default]
     Day,
-    Night
+    Dusk,
+    Night,
+    WitchingHour,
+    Dawn,
+}
+
+impl TimeOfDay {
+    /// Whether crows are out roosting on ruins and pestering villagers.
+    pub fn is_dark(&self) -> bool {
+        matches!(self, TimeOfDay::Night | TimeOfDay::WitchingHour)
+    }
+
+    fn sky_color(&self) -> Color {
+        match self {
+            TimeOfDay::Day => Color::srgba(0.397, 0.383, 0.473, 1.0),
+            TimeOfDay::Dusk => Color::srgba(0.452, 0.281, 0.318, 1.0),
+            TimeOfDay::Night => Color::srgba(0.072, 0.071, 0.072, 1.0),
+            TimeOfDay::WitchingHour => Color::srgba(0.058, 0.092, 0.052, 1.0),
+            TimeOfDay::Dawn => Color::srgba(0.548, 0.421, 0.452, 1.0),
+        }
+    }
+
+    fn star_brightness(&self) -> f32 {
+        match self {
+            TimeOfDay::Day => 0.0,
+            TimeOfDay::Dusk => 0.3,
+            TimeOfDay::Night => 1.0,
+            TimeOfDay::WitchingHour => 0.7,
+            TimeOfDay::Dawn => 0.15,
+        }
+    }
+
+    /// Color and brightness for the `AmbientLight`.
+    fn ambient(&self) -> (Color, f32) {
+        match self {
+            TimeOfDay::Day => (Color::WHITE, 1000.0),
+            TimeOfDay::Dusk => (Color::srgb(1.0, 0.75, 0.6), 700.0),
+            TimeOfDay::Night => (Color::srgb(0.6, 0.65, 1.0), 400.0),
+            TimeOfDay::WitchingHour => (Color::srgb(0.6, 1.0, 0.6), 300.0),
+            TimeOfDay::Dawn => (Color::srgb(1.0, 0.8, 0.85), 800.0),
+        }
+    }
+
+    /// How long the sky takes to turn to this time of day.
+    fn transition(&self) -> Duration {
+        match self {
+            TimeOfDay::Day | TimeOfDay::Dusk | TimeOfDay::Night => Duration::from_secs(2),
+            TimeOfDay::WitchingHour => Duration::from_secs(4),
+            TimeOfDay::Dawn => Duration::from_secs(3),
+        }
+    }
 }
 
 #[derive(Component, Reflect)]
@@ -45,30 +90,22 @@ pub enum Sky {
     },
 }
 
-#[derive(Resource)]
-struct CloudsContainer(Entity);
-
 impl Sky {
-    pub fn to_night(&mut self, now: std::time::Duration) {
-        let color = self.current_color(now);
-        *self = Sky::Transition {
-            start_color: color,
-            end_color: Color::srgba(0.072, 0.071, 0.072, 1.0),
-            start_time: now,
-            end_time: now + std::time::Duration::from_secs(2),
-            start_star_brightness: self.current_star_brightness(now),
-            end_star_brightness: 1.0,
-        };
-    }
-    pub fn to_day(&mut self, now: std::time::Duration) {
+    /// Fades to `time_of_day`'s colors, or to `zenith` if the level has its own.
+    pub fn transition_to(
+        &mut self,
+        time_of_day: TimeOfDay,
+        zenith: Option<Color>,
+        now: std::time::Duration,
+    ) {
         let color = self.current_color(now);
         *self = Sky::Transition {
             start_color: color,
-            end_color: Color::srgba(0.397, 0.383, 0.473, 1.0),
+            end_color: zenith.unwrap_or(time_of_day.sky_color()),
             start_time: now,
-            end_time: now + std::time::Duration::from_secs(2),
+            end_time: now + time_of_day.transition(),
             start_star_brightness: self.current_star_brightness(now),
-            end_star_brightness: 0.0,
+            end_star_brightness: time_of_day.star_brightness(),
         };
     }
 
@@ -117,20 +154,54 @@ impl Plugin for EnvironmentalDecorationPlugin {
             .register_type::<Water>()
             .insert_resource(TimeOfDay::Day)
             .add_systems(
-                Startup, make_cloud_container
+                Update,
+                (water_animation_control, star_animation),
             )
             .add_systems(
                 Update,
-                (maintain_clouds.run_if(on_timer(Duration::from_millis(1000))), move_clouds.run_if(on_timer(Duration::from_millis(16))), water_animation_control, star_animation, sky_color_animation),
-            );
+                (
+                    follow_time_of_day.run_if(resource_changed::<TimeOfDay>),
+                    ambient_light,
+                ),
+            )
+            .add_systems(OnEnter(GameState::BuildPhase), set_time_of_day(TimeOfDay::Dusk))
+            .add_systems(OnEnter(GameState::DecayPhase), set_time_of_day(TimeOfDay::WitchingHour))
+            .add_systems(OnEnter(GameState::ScoringPhase), set_time_of_day(TimeOfDay::Dawn));
     }
 }
 
-fn make_cloud_container(
-    mut commands: Commands,
+fn set_time_of_day(to: TimeOfDay) -> impl Fn(ResMut<TimeOfDay>) {
+    move |mut time_of_day: ResMut<TimeOfDay>| {
+        time_of_day.set_if_neq(to);
+    }
+}
+
+fn follow_time_of_day(
+    time_of_day: Res<TimeOfDay>,
+    level: Res<CurrentLevel>,
+    mut sky: Query<&mut Sky>,
+    time: Res<Time>,
 ) {
-    let id = commands.spawn(()).id();
-    commands.insert_resource(CloudsContainer(id));
+    let zenith = level
+        .sky(*time_of_day)
+        .map(|colors| Color::srgb_from_array(colors.zenith));
+    for mut sky in &mut sky {
+        sky.transition_to(*time_of_day, zenith, time.elapsed());
+    }
+}
+
+fn ambient_light(
+    mut ambient: ResMut<AmbientLight>,
+    time_of_day: Res<TimeOfDay>,
+    time: Res<Time>,
+) {
+    let (color, brightness) = time_of_day.ambient();
+    if (ambient.brightness - brightness).abs() < 1.0 {
+        return;
+    }
+    let f = (time.delta_seconds() / time_of_day.transition().as_secs_f32() * 4.0).min(1.0);
+    ambient.color = ambient.color.mix(&color, f);
+    ambient.brightness += (brightness - ambient.brightness) * f;
 }
 
 fn water_animation_control(
@@ -149,32 +220,6 @@ fn water_animation_control(
     }
 }
 
-fn sky_color_animation(
-    mut query: Query<(&mut Sky, &Handle<StandardMaterial>), Without<Star>>,
-    time: Res<Time>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    for (mut sky_state, material_handle) in &mut query {
-        if let Some(material) = materials.get_mut(material_handle) {
-            match &*sky_state {
-                Sky::Color(color, _) => material.emissive = (*color).into(),
-                Sky::Transition {
-                    end_color,
-                    end_time,
-                    end_star_brightness,
-                    ..
-                } => {
-                    let now = time.elapsed();
-                    if *end_time <= now {
-                        *sky_state = Sky::Color(*end_color, *end_star_brightness);
-                    }
-                    material.emissive = sky_state.current_color(now).into();
-                }
-            }
-        }
-    }
-}
-
 fn star_animation(
     mut query: Query<(&mut Sky, &Handle<StandardMaterial>), With<Star>>,
     time: Res<Time>,
@@ -205,41 +250,3 @@ fn star_animation(
         }
     }
 }
-
-fn maintain_clouds(
-    mut commands: Commands,
-    query: Query<(Entity, &Transform), With<Cloud>>,
-    clouds: Res<CloudsContainer>,
-) {
-    let mut count = 0;
-    for (entity, transform) in &query {
-        if transform.translation.x > 34.0 {
-            commands.entity(entity).despawn_recursive();
-        } else {
-            count += 1;
-        }
-    }
-
-    if count < 10 && fastrand::f32() > 0.3 {
-        let mut scale = Vec3::splat(fastrand::u32(90..120) as f32 / 100.0);
-        scale.x = fastrand::u32(100..250) as f32 / 100.0;
-        let transform = Transform::from_translation(Vec3::new(fastrand::i32(-60..-40) as f32, fastrand::i32(0..23) as f32, -7.0)).with_scale(scale);
-        let path = CLOUDS[fastrand::usize(0..CLOUDS.len())];
-        commands.entity(clouds.0).with_children(|commands| {commands.spawn((
-            transform,
-            BlueprintInfo::from_path(path),
-            SpawnBlueprint,
-            HideUntilReady,
-            GameWorldTag,
-            Cloud,
-        ));});
-    }
-}
-
-fn move_clouds(
-    mut query: Query<&mut Transform, With<Cloud>>,
-) {
-    for mut transform in &mut query {
-        transform.translation.x += 0.1 + 0.05*(transform.translation.y/23.0);
-    }
-}
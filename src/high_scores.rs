@@ -0,0 +1,402 @@
+use bevy::prelude::*;
+use blenvy::{BlueprintInfo, BlueprintInstanceReady, GameWorldTag, HideUntilReady, SpawnBlueprint};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    achievements::RoundScored,
+    block::Block,
+    block_pool::BlockPoolResident,
+    build_phase::OnTentacle,
+    decay_phase::{Decayed, RuinedVariant},
+    levels::CurrentLevel,
+    residents::Resident,
+    scoring::{ScoreBreakdown, ScoreCategory},
+    storage,
+    villagers::{Fleeing, Villager},
+    GameState, RoundSeed,
+};
+
+const MAX_ENTRIES: usize = 10;
+const SHOWN_ENTRIES: usize = 5;
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BlockSnapshot {
+    pub path: String,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    pub decayed: bool,
+    /// Decayed without a ruined variant, so it has to be desaturated when shown again.
+    pub desaturated: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RoundStats {
+    pub blocks: usize,
+    pub decayed: usize,
+    pub residents: usize,
+    pub villagers: usize,
+    pub categories: Vec<(String, i32)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighScoreEntry {
+    pub score: i32,
+    pub date: u64,
+    pub seed: u64,
+    pub stats: RoundStats,
+    pub castle: Vec<BlockSnapshot>,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct HighScores {
+    /// The best castles, highest score first.
+    pub entries: Vec<HighScoreEntry>,
+    /// Every score ever recorded, for averages and ranking.
+    pub all_scores: Vec<i32>,
+}
+
+impl HighScores {
+    pub fn best(&self) -> Option<i32> {
+        self.all_scores.iter().copied().max()
+    }
+
+    pub fn average(&self) -> f32 {
+        if self.all_scores.is_empty() {
+            0.0
+        } else {
+            self.all_scores.iter().sum::<i32>() as f32 / self.all_scores.len() as f32
+        }
+    }
+
+    pub fn rank(&self, score: i32) -> usize {
+        self.all_scores.iter().filter(|s| **s > score).count() + 1
+    }
+
+    fn record(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        self.all_scores.push(entry.score);
+        let idx = self.entries.partition_point(|e| e.score >= entry.score);
+        if idx >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(idx, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(idx)
+    }
+}
+
+/// `None` until this round's score has been recorded, then the slot it took in the table, if any.
+#[derive(Resource, Default)]
+pub struct RoundRecorded(pub Option<Option<usize>>);
+
+#[derive(Component)]
+struct HighScorePanel;
+
+#[derive(Component)]
+struct ViewSnapshot(Option<usize>);
+
+#[derive(Component)]
+struct SnapshotBlock;
+
+#[derive(Component)]
+struct SnapshotDesaturated;
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundRecorded>()
+            .init_resource::<HighScores>()
+            .add_systems(Update, load.run_if(resource_changed::<CurrentLevel>))
+            .add_systems(
+                Update,
+                (
+                    record_score,
+                    show_panel,
+                    view_button_system,
+                    desaturate_snapshots,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::ScoringPhase)),
+            )
+            .add_systems(
+                OnEnter(GameState::ScoringPhase),
+                |mut recorded: ResMut<RoundRecorded>| recorded.0 = None,
+            )
+            .add_systems(OnExit(GameState::ScoringPhase), cleanup);
+    }
+}
+
+fn storage_key(level: &str) -> String {
+    format!("high_scores.{level}")
+}
+
+/// The high score table for the level with id `level`.
+pub fn load_scores(level: &str) -> HighScores {
+    storage::load_json(&storage_key(level)).unwrap_or_default()
+}
+
+fn load(mut high_scores: ResMut<HighScores>, level: Res<CurrentLevel>) {
+    if level.def.is_some() {
+        *high_scores = load_scores(&level.id);
+    }
+}
+
+pub fn record_score(
+    breakdown: Res<ScoreBreakdown>,
+    mut recorded: ResMut<RoundRecorded>,
+    mut high_scores: ResMut<HighScores>,
+    seed: Res<RoundSeed>,
+    level: Res<CurrentLevel>,
+    blocks: Query<
+        (
+            &BlueprintInfo,
+            &GlobalTransform,
+            Option<&Decayed>,
+            Option<&RuinedVariant>,
+        ),
+        (With<Block>, Without<BlockPoolResident>, Without<OnTentacle>),
+    >,
+    residents: Query<(), With<Resident>>,
+    villagers: Query<(), (With<Villager>, Without<Fleeing>)>,
+    mut scored: EventWriter<RoundScored>,
+) {
+    if recorded.0.is_some() || breakdown.revealed < breakdown.entries.len() {
+        return;
+    }
+
+    let castle: Vec<_> = blocks
+        .iter()
+        .map(|(info, transform, decayed, ruined)| {
+            let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+            BlockSnapshot {
+                path: info.path.clone(),
+                translation: translation.to_array(),
+                rotation: rotation.to_array(),
+                scale: scale.to_array(),
+                decayed: decayed.is_some(),
+                desaturated: decayed.is_some() && ruined.is_none(),
+            }
+        })
+        .collect();
+    let stats = RoundStats {
+        blocks: castle.len(),
+        decayed: castle.iter().filter(|b| b.decayed).count(),
+        residents: residents.iter().count(),
+        villagers: villagers.iter().count(),
+        categories: ScoreCategory::ALL
+            .iter()
+            .map(|c| (c.label().to_string(), breakdown.category_total(*c)))
+            .collect(),
+    };
+    scored.send(RoundScored {
+        score: breakdown.total(),
+        intact: stats.blocks - stats.decayed,
+        decayed: stats.decayed,
+    });
+    let entry = HighScoreEntry {
+        score: breakdown.total(),
+        date: storage::now(),
+        seed: seed.0,
+        stats,
+        castle,
+    };
+    recorded.0 = Some(high_scores.record(entry));
+    storage::save_json(&storage_key(&level.id), &*high_scores);
+}
+
+fn show_panel(
+    mut commands: Commands,
+    recorded: Res<RoundRecorded>,
+    high_scores: Res<HighScores>,
+    level: Res<CurrentLevel>,
+    panel: Query<(), With<HighScorePanel>>,
+) {
+    let Some(this_round) = recorded.0 else {
+        return;
+    };
+    if !panel.is_empty() {
+        return;
+    }
+
+    let text_style = |highlight: bool| TextStyle {
+        font_size: 22.0,
+        color: if highlight {
+            Color::srgb(1.0, 0.85, 0.4)
+        } else {
+            Color::srgb(0.9, 0.9, 0.9)
+        },
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(24.0),
+                    top: Val::Px(24.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            HighScorePanel,
+        ))
+        .with_children(|parent| {
+            let title = match &level.def {
+                Some(def) => format!("High Scores: {}", def.name),
+                None => "High Scores".to_string(),
+            };
+            parent.spawn(TextBundle::from_section(title, text_style(false)));
+            for (i, entry) in high_scores.entries.iter().take(SHOWN_ENTRIES).enumerate() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(12.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!(
+                                "{}. {}  {}",
+                                i + 1,
+                                entry.score,
+                                storage::format_date(entry.date)
+                            ),
+                            text_style(this_round == Some(i)),
+                        ));
+                        spawn_button(parent, "View", ViewSnapshot(Some(i)));
+                    });
+            }
+            spawn_button(parent, "This castle", ViewSnapshot(None));
+        });
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, view: ViewSnapshot) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                border_color: BorderColor(Color::BLACK),
+                border_radius: BorderRadius::MAX,
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            view,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::srgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn view_button_system(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &ViewSnapshot,
+            &mut BackgroundColor,
+            &mut BorderColor,
+        ),
+        Changed<Interaction>,
+    >,
+    high_scores: Res<HighScores>,
+    snapshot: Query<Entity, With<SnapshotBlock>>,
+    mut castle: Query<
+        &mut Visibility,
+        (With<Block>, Without<BlockPoolResident>, Without<OnTentacle>),
+    >,
+) {
+    for (interaction, view, mut color, mut border_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                for entity in &snapshot {
+                    commands.entity(entity).despawn_recursive();
+                }
+                let entry = view.0.and_then(|i| high_scores.entries.get(i));
+                for mut visibility in &mut castle {
+                    *visibility = if entry.is_some() {
+                        Visibility::Hidden
+                    } else {
+                        Visibility::Visible
+                    };
+                }
+                for block in entry.iter().flat_map(|e| &e.castle) {
+                    let mut entity = commands.spawn((
+                        Transform {
+                            translation: Vec3::from_array(block.translation),
+                            rotation: Quat::from_array(block.rotation),
+                            scale: Vec3::from_array(block.scale),
+                        },
+                        BlueprintInfo::from_path(&block.path),
+                        SpawnBlueprint,
+                        HideUntilReady,
+                        GameWorldTag,
+                        SnapshotBlock,
+                    ));
+                    if block.desaturated {
+                        entity.insert(SnapshotDesaturated);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+                border_color.0 = Color::WHITE;
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+                border_color.0 = Color::BLACK;
+            }
+        }
+    }
+}
+
+fn desaturate_snapshots(
+    query: Query<Entity, (With<SnapshotDesaturated>, Added<BlueprintInstanceReady>)>,
+    children: Query<&Children>,
+    mut material_handle: Query<&mut Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for entity in &query {
+        for entity in children.iter_descendants(entity) {
+            if let Ok(mut handle) = material_handle.get_mut(entity) {
+                if let Some(mut material) = materials.get(&*handle).cloned() {
+                    let hsv: Hsva = material.emissive.into();
+                    material.emissive = hsv.with_saturation(0.2).with_value(0.2).into();
+                    *handle = materials.add(material);
+                }
+            }
+        }
+    }
+}
+
+fn cleanup(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<HighScorePanel>, With<SnapshotBlock>)>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+/// Simulation steps per second. Per-step tuning values (crow steering) were
+/// authored against this rate.
+pub const SIM_HZ: f64 = 60.0;
+
+/// Smooths the translation of an entity simulated in `FixedUpdate` between steps. During the
+/// fixed steps `Transform` holds the simulated translation, the rest of the frame it holds
+/// one blended between the last two steps.
+#[derive(Component, Default)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+}
+
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_hz(SIM_HZ))
+            .add_systems(FixedFirst, restore_simulated)
+            .add_systems(FixedLast, record_simulated)
+            .add_systems(
+                PostUpdate,
+                interpolate.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// How many steps at `SIM_HZ` the last fixed step covered, for scaling per-step values.
+pub fn sim_steps(time: &Time) -> f32 {
+    time.delta_seconds() * SIM_HZ as f32
+}
+
+fn restore_simulated(mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    for (mut transform, mut interpolated) in &mut query {
+        if interpolated.is_added() {
+            interpolated.current = transform.translation;
+        } else {
+            transform.translation = interpolated.current;
+        }
+        interpolated.previous = interpolated.current;
+    }
+}
+
+fn record_simulated(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut query {
+        interpolated.current = transform.translation;
+    }
+}
+
+fn interpolate(
+    mut query: Query<(&mut Transform, &mut Interpolated)>,
+    time: Res<Time<Fixed>>,
+) {
+    let s = time.overstep_fraction();
+    for (mut transform, mut interpolated) in &mut query {
+        if interpolated.is_added() {
+            // Hasn't been through a fixed step yet
+            interpolated.previous = transform.translation;
+            interpolated.current = transform.translation;
+            continue;
+        }
+        transform.translation = interpolated.previous.lerp(interpolated.current, s);
+    }
+}
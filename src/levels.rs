@@ -0,0 +1,415 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    render::render_resource::Face,
+    utils::HashMap,
+};
+use blenvy::{BlueprintInfo, BlueprintInstanceReady, GameWorldTag, HideUntilReady, SpawnBlueprint};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    build_phase::{Dead, Extending, Idle, Retracting, Tentacle},
+    crow::{Employed, Perched},
+    environmental_decoration::{TimeOfDay, Water},
+    high_scores,
+    water::{replace_water_material, WaterMaterial},
+    GameState, LEVELS,
+};
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SkyColors {
+    pub zenith: [f32; 3],
+    pub horizon: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SkyPalette {
+    pub day: SkyColors,
+    pub dusk: SkyColors,
+    pub night: SkyColors,
+    pub witching_hour: SkyColors,
+    pub dawn: SkyColors,
+}
+
+impl SkyPalette {
+    pub fn get(&self, time_of_day: TimeOfDay) -> &SkyColors {
+        match time_of_day {
+            TimeOfDay::Day => &self.day,
+            TimeOfDay::Dusk => &self.dusk,
+            TimeOfDay::Night => &self.night,
+            TimeOfDay::WitchingHour => &self.witching_hour,
+            TimeOfDay::Dawn => &self.dawn,
+        }
+    }
+}
+
+/// A foundation to build on. The blueprint brings the island's shape, anchors and tentacles,
+/// the rest adjusts it. `mirrored` flips the island left to right, `tentacles` limits how
+/// many of the blueprint's tentacles offer blocks and `sky` replaces the usual sky colors.
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug)]
+pub struct LevelDef {
+    pub name: String,
+    pub foundation: String,
+    #[serde(default)]
+    pub mirrored: bool,
+    #[serde(default)]
+    pub water_level: f32,
+    #[serde(default)]
+    pub tentacles: Option<usize>,
+    #[serde(default)]
+    pub sky: Option<SkyPalette>,
+}
+
+#[derive(Default)]
+struct LevelDefLoader;
+
+impl AssetLoader for LevelDefLoader {
+    type Asset = LevelDef;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.json"]
+    }
+}
+
+/// The level being played, `def` is `None` until one has been chosen.
+#[derive(Resource, Default)]
+pub struct CurrentLevel {
+    /// The level file's name without the extension, used to key its high scores.
+    pub id: String,
+    pub def: Option<LevelDef>,
+    foundation: Option<Entity>,
+}
+
+impl CurrentLevel {
+    pub fn sky(&self, time_of_day: TimeOfDay) -> Option<&SkyColors> {
+        Some(self.def.as_ref()?.sky.as_ref()?.get(time_of_day))
+    }
+}
+
+/// A tentacle the current level doesn't use, left dead through every build phase.
+#[derive(Component)]
+pub struct SpareTentacle;
+
+#[derive(Component)]
+struct LevelFoundation;
+
+/// A foundation spawned with a negative x scale. That turns the winding of every triangle
+/// under it around, so its meshes need materials that cull the other side.
+#[derive(Component)]
+struct Mirrored;
+
+/// A mesh under a mirrored foundation that already has its material flipped.
+#[derive(Component)]
+struct MirroredMaterial;
+
+#[derive(Resource)]
+struct Levels(Vec<Handle<LevelDef>>);
+
+#[derive(Component)]
+struct LevelSelectPanel;
+
+#[derive(Component)]
+struct LevelButton(usize);
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LevelDef>()
+            .init_asset_loader::<LevelDefLoader>()
+            .init_resource::<CurrentLevel>()
+            .add_systems(Startup, load)
+            .add_systems(
+                Update,
+                (show_panel, level_buttons, prepare_foundation)
+                    .chain()
+                    .run_if(in_state(GameState::LevelSelect)),
+            )
+            .add_systems(Update, mirror_materials.before(replace_water_material))
+            .add_systems(OnExit(GameState::LevelSelect), cleanup);
+    }
+}
+
+fn load(mut commands: Commands, assets: Res<AssetServer>) {
+    commands.insert_resource(Levels(
+        LEVELS.iter().map(|path| assets.load(*path)).collect(),
+    ));
+}
+
+fn level_id(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.strip_suffix(".level.json").unwrap_or(name)
+}
+
+fn show_panel(
+    mut commands: Commands,
+    levels: Res<Levels>,
+    defs: Res<Assets<LevelDef>>,
+    current: Res<CurrentLevel>,
+    panel: Query<(), With<LevelSelectPanel>>,
+) {
+    if !panel.is_empty() || levels.0.iter().any(|handle| !defs.contains(handle)) {
+        return;
+    }
+
+    let text_style = |size: f32| TextStyle {
+        font_size: size,
+        color: Color::srgb(0.9, 0.9, 0.9),
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            LevelSelectPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Choose a foundation",
+                text_style(32.0),
+            ));
+            for (i, (path, handle)) in LEVELS.iter().zip(&levels.0).enumerate() {
+                let Some(def) = defs.get(handle) else {
+                    continue;
+                };
+                let id = level_id(path);
+                let best = high_scores::load_scores(id)
+                    .best()
+                    .map_or("no score yet".to_string(), |best| format!("best {best}"));
+                let label = if current.def.is_some() && current.id == id {
+                    format!("{}  ({best}, current)", def.name)
+                } else {
+                    format!("{}  ({best})", def.name)
+                };
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(360.0),
+                                padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                                border: UiRect::all(Val::Px(2.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            border_color: BorderColor(Color::BLACK),
+                            border_radius: BorderRadius::MAX,
+                            background_color: NORMAL_BUTTON.into(),
+                            ..default()
+                        },
+                        LevelButton(i),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, text_style(22.0)));
+                    });
+            }
+        });
+}
+
+/// Picking the level already on screen builds on the same foundation again, picking another
+/// swaps in the new foundation. Either way the last castle was cleared away when scoring
+/// ended.
+fn level_buttons(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &LevelButton,
+            &mut BackgroundColor,
+            &mut BorderColor,
+        ),
+        Changed<Interaction>,
+    >,
+    levels: Res<Levels>,
+    defs: Res<Assets<LevelDef>>,
+    mut current: ResMut<CurrentLevel>,
+    perched: Query<Entity, With<Perched>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, button, mut color, mut border_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                let Some(def) = defs.get(&levels.0[button.0]) else {
+                    continue;
+                };
+                let id = level_id(LEVELS[button.0]);
+                if current.def.is_some() && current.id == id {
+                    next_state.set(GameState::BuildPhase);
+                    return;
+                }
+                if let Some(foundation) = current.foundation.take() {
+                    commands.entity(foundation).despawn_recursive();
+                    for entity in &perched {
+                        commands
+                            .entity(entity)
+                            .remove::<Perched>()
+                            .remove::<Employed>();
+                    }
+                }
+                let scale = Vec3::new(if def.mirrored { -1.0 } else { 1.0 }, 1.0, 1.0);
+                let mut foundation = commands.spawn((
+                    BlueprintInfo::from_path(&def.foundation),
+                    Transform::from_scale(scale),
+                    SpawnBlueprint,
+                    HideUntilReady,
+                    GameWorldTag,
+                    LevelFoundation,
+                ));
+                if def.mirrored {
+                    foundation.insert(Mirrored);
+                }
+                current.foundation = Some(foundation.id());
+                current.id = id.to_string();
+                current.def = Some(def.clone());
+                return;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+                border_color.0 = Color::WHITE;
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+                border_color.0 = Color::BLACK;
+            }
+        }
+    }
+}
+
+/// Once the new foundation has spawned, raises or lowers its water, benches the tentacles the
+/// level doesn't use and starts building.
+fn prepare_foundation(
+    mut commands: Commands,
+    foundations: Query<Entity, (With<LevelFoundation>, Added<BlueprintInstanceReady>)>,
+    children: Query<&Children>,
+    mut water: Query<&mut Transform, With<Water>>,
+    tentacles: Query<&GlobalTransform, With<Tentacle>>,
+    current: Res<CurrentLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(def) = &current.def else {
+        return;
+    };
+    for foundation in &foundations {
+        let mut found = vec![];
+        for entity in children.iter_descendants(foundation) {
+            if let Ok(mut transform) = water.get_mut(entity) {
+                transform.translation.y += def.water_level;
+            }
+            if let Ok(transform) = tentacles.get(entity) {
+                found.push((entity, transform.translation().x));
+            }
+        }
+
+        // Keep an even spread across the island rather than the first few
+        found.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let keep = def.tentacles.unwrap_or(found.len()).min(found.len());
+        let kept: Vec<usize> = (0..keep)
+            .map(|i| (i * found.len() + found.len() / 2) / keep)
+            .collect();
+        for (i, (entity, _)) in found.iter().enumerate() {
+            if !kept.contains(&i) {
+                commands
+                    .entity(*entity)
+                    .remove::<(Extending, Retracting, Idle)>()
+                    .insert((SpareTentacle, Dead, Visibility::Hidden));
+            }
+        }
+        next_state.set(GameState::BuildPhase);
+    }
+}
+
+fn flip(cull_mode: Option<Face>) -> Option<Face> {
+    match cull_mode {
+        Some(Face::Back) => Some(Face::Front),
+        Some(Face::Front) => Some(Face::Back),
+        None => None,
+    }
+}
+
+/// Gives every mesh under a mirrored foundation a copy of its material culling the other
+/// side, otherwise the insides of the island show instead of its outsides. Keeps watching
+/// so meshes that load or get their material swapped later are flipped too. Runs before the
+/// water takes over its meshes' materials so it copies the flipped ones.
+#[allow(clippy::too_many_arguments)]
+fn mirror_materials(
+    mut commands: Commands,
+    foundations: Query<Entity, With<Mirrored>>,
+    children: Query<&Children>,
+    mut standard: Query<&mut Handle<StandardMaterial>, Without<MirroredMaterial>>,
+    mut water: Query<&mut Handle<WaterMaterial>, Without<MirroredMaterial>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    mut water_materials: ResMut<Assets<WaterMaterial>>,
+    mut flipped_standard: Local<HashMap<AssetId<StandardMaterial>, Handle<StandardMaterial>>>,
+    mut flipped_water: Local<HashMap<AssetId<WaterMaterial>, Handle<WaterMaterial>>>,
+) {
+    for foundation in &foundations {
+        for entity in children.iter_descendants(foundation) {
+            if let Ok(mut handle) = standard.get_mut(entity) {
+                let flipped = match flipped_standard.get(&handle.id()) {
+                    Some(flipped) => flipped.clone(),
+                    None => {
+                        // Not loaded yet, try again next frame
+                        let Some(mut material) = standard_materials.get(&*handle).cloned() else {
+                            continue;
+                        };
+                        material.cull_mode = flip(material.cull_mode);
+                        let flipped = standard_materials.add(material);
+                        flipped_standard.insert(handle.id(), flipped.clone());
+                        flipped
+                    }
+                };
+                *handle = flipped;
+                commands.entity(entity).insert(MirroredMaterial);
+            } else if let Ok(mut handle) = water.get_mut(entity) {
+                let flipped = match flipped_water.get(&handle.id()) {
+                    Some(flipped) => flipped.clone(),
+                    None => {
+                        let Some(mut material) = water_materials.get(&*handle).cloned() else {
+                            continue;
+                        };
+                        material.base.cull_mode = flip(material.base.cull_mode);
+                        let flipped = water_materials.add(material);
+                        flipped_water.insert(handle.id(), flipped.clone());
+                        flipped
+                    }
+                };
+                *handle = flipped;
+                commands.entity(entity).insert(MirroredMaterial);
+            }
+        }
+    }
+}
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<LevelSelectPanel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
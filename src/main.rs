@@ -2,6 +2,7 @@ use serde_json::Value;
 
 use bevy::{
     asset::AssetMetaCheck,
+    ecs::system::SystemParam,
     gltf::GltfExtras,
     log::{Level, LogPlugin},
     prelude::*,
@@ -11,14 +12,32 @@ use bevy_kira_audio::prelude::*;
 use bevy_mod_picking::prelude::*;
 use blenvy::*;
 
+mod achievements;
 mod block;
 mod block_pool;
 mod build_phase;
+mod clouds;
+mod debris;
 mod decay_phase;
 mod environmental_decoration;
+mod high_scores;
+mod interpolation;
+mod levels;
 mod music;
+mod residents;
+mod scoring;
 mod scoring_phase;
+mod settings;
+mod silhouette;
+mod sky;
+mod stems;
+mod storage;
+mod villagers;
+mod water;
+mod weather;
 mod crow;
+mod crow_jobs;
+mod crow_profiles;
 
 const SNAP_DISTANCE: f32 = 30.0;
 include!(concat!(env!("OUT_DIR"), "/consts.rs"));
@@ -26,6 +45,7 @@ include!(concat!(env!("OUT_DIR"), "/consts.rs"));
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
 enum GameState {
     Loading,
+    LevelSelect,
     BuildPhase,
     DecayPhase,
     ScoringPhase,
@@ -34,6 +54,13 @@ enum GameState {
 #[derive(Resource)]
 struct CameraScale(f32);
 
+/// Where the camera should pan and zoom to, `None` for the default framing of the castle.
+#[derive(Resource)]
+pub struct CameraFocus {
+    pub target: Option<(Vec2, f32)>,
+    current: (Vec2, f32),
+}
+
 #[derive(Component)]
 struct LoadingScreen;
 
@@ -57,9 +84,50 @@ pub struct SavedPosition(Transform);
 #[derive(Default, Resource)]
 pub struct MousePos(Vec2);
 
+/// Left clicks aimed at the castle rather than at a button or slider laid over it.
+#[derive(SystemParam)]
+pub struct WorldClicks<'w, 's> {
+    mouse_button_input: Res<'w, ButtonInput<MouseButton>>,
+    interactions: Query<'w, 's, &'static Interaction>,
+}
+
+impl WorldClicks<'_, '_> {
+    /// Whether the pointer is over a piece of UI that reacts to it.
+    pub fn over_ui(&self) -> bool {
+        self.interactions.iter().any(|i| *i != Interaction::None)
+    }
+
+    pub fn just_pressed(&self) -> bool {
+        self.mouse_button_input.just_pressed(MouseButton::Left) && !self.over_ui()
+    }
+
+    pub fn just_released(&self) -> bool {
+        self.mouse_button_input.just_released(MouseButton::Left) && !self.over_ui()
+    }
+}
+
+/// Seed for the round, picked fresh at the start of every round.
+#[derive(Default, Resource)]
+pub struct RoundSeed(pub u64);
+
+/// Rolls that shape the round, such as the blocks offered, the weather and who moves into the
+/// ruins, are drawn from here so that the same seed gives the same rolls. Purely cosmetic
+/// randomness uses `fastrand`'s thread local rng instead.
+#[derive(Resource)]
+pub struct RoundRng(pub fastrand::Rng);
+
 #[derive(Resource)]
 pub struct PaperTexture(Handle<Image>);
 
+/// The row along the bottom left corner that the settings and achievements buttons sit in.
+#[derive(Component)]
+pub struct CornerButtons;
+
+/// A panel opened from one of the `CornerButtons`. They all open in the same spot above the
+/// row, so opening one closes whichever other is open.
+#[derive(Component)]
+pub struct CornerPanel;
+
 #[derive(Copy, Clone, Component)]
 struct Lift<T>(std::marker::PhantomData<T>);
 impl<T> Default for Lift<T> {
@@ -73,7 +141,13 @@ fn main() {
         .register_type::<SpawnedFrom>()
         .register_type::<Spawner>()
         .init_resource::<MousePos>()
+        .init_resource::<RoundSeed>()
+        .insert_resource(RoundRng(fastrand::Rng::new()))
         .insert_resource(CameraScale(1.0))
+        .insert_resource(CameraFocus {
+            target: None,
+            current: (Vec2::ZERO, 1.0),
+        })
         .add_plugins(
             DefaultPlugins
                 .set(low_latency_window_plugin())
@@ -106,11 +180,26 @@ fn main() {
         .add_plugins(crate::block::BlockPlugin)
         .add_plugins(block_pool::BlockPoolPlugin)
         .add_plugins(music::AudioPlugin)
+        .add_plugins(settings::SettingsPlugin)
+        .add_plugins(stems::StemsPlugin)
         .add_plugins(crate::environmental_decoration::EnvironmentalDecorationPlugin)
+        .add_plugins(weather::WeatherPlugin)
+        .add_plugins(water::WaterPlugin)
+        .add_plugins(sky::SkyPlugin)
+        .add_plugins(clouds::CloudsPlugin)
+        .add_plugins(levels::LevelPlugin)
         .add_plugins(build_phase::BuildPhasePlugin)
+        .add_plugins(interpolation::InterpolationPlugin)
         .add_plugins(crow::CrowPlugin)
+        .add_plugins(crow_jobs::CrowJobPlugin)
+        .add_plugins(crow_profiles::CrowProfilesPlugin)
         .add_plugins(decay_phase::DecayPhasePlugin)
+        .add_plugins(debris::DebrisPlugin)
+        .add_plugins(residents::ResidentPlugin)
+        .add_plugins(villagers::VillagerPlugin)
         .add_plugins(scoring_phase::ScoringPhasePlugin)
+        .add_plugins(high_scores::HighScorePlugin)
+        .add_plugins(achievements::AchievementPlugin)
         .insert_resource(AmbientLight {
             color: Color::WHITE,
             brightness: 1000.,
@@ -119,6 +208,7 @@ fn main() {
             Update,
             (
                 maintain_camera_scale,
+                focus_camera,
                 update_mouse_pos,
                 check_for_gltf_extras,
                 fix_materials,
@@ -128,10 +218,16 @@ fn main() {
             PostUpdate,
             check_loading_completion.run_if(in_state(GameState::Loading)),
         )
-        .add_systems(Startup, (blank_screen, start_load))
+        .add_systems(OnEnter(GameState::BuildPhase), reseed_round)
+        .add_systems(Startup, (blank_screen, start_load, spawn_corner_buttons))
         .run();
 }
 
+pub fn reseed_round(mut seed: ResMut<RoundSeed>, mut rng: ResMut<RoundRng>) {
+    seed.0 = fastrand::u64(..);
+    rng.0 = fastrand::Rng::with_seed(seed.0);
+}
+
 fn start_load(mut commands: Commands, assets: ResMut<AssetServer>) {
     commands.insert_resource(PaperTexture(
         assets.load("indieground-vintagepaper-textures-03.jpg"),
@@ -175,16 +271,32 @@ fn maintain_camera_scale(
     mut projection: Query<&mut Projection>,
     mut camera_scale: ResMut<CameraScale>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
+    focus: Res<CameraFocus>,
 ) {
     let window = q_windows.single();
     for mut projection in &mut projection {
         if let Projection::Orthographic(projection) = &mut *projection {
-            projection.scale = 44.0/ window.height();
+            projection.scale = 44.0 * focus.current.1 / window.height();
             camera_scale.0 = projection.scale;
         }
     }
 }
 
+fn focus_camera(
+    mut focus: ResMut<CameraFocus>,
+    mut cameras: Query<&mut Transform, With<Camera3d>>,
+    time: Res<Time>,
+) {
+    let (target, zoom) = focus.target.unwrap_or((Vec2::ZERO, 1.0));
+    let f = 1.0 - (-4.0 * time.delta_seconds()).exp();
+    focus.current.0 = focus.current.0.lerp(target, f);
+    focus.current.1 += (zoom - focus.current.1) * f;
+    for mut transform in &mut cameras {
+        transform.translation.x = focus.current.0.x;
+        transform.translation.y = focus.current.0.y;
+    }
+}
+
 fn check_loading_completion(
     mut commands: Commands,
     query: Query<
@@ -203,19 +315,37 @@ fn check_loading_completion(
     if query.is_empty() {
         let entity = loading_screen.single();
         commands.entity(entity).despawn_recursive();
-        next_state.set(GameState::BuildPhase);
+        next_state.set(GameState::LevelSelect);
     }
 }
 
+pub fn spawn_corner_buttons(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(16.0),
+                bottom: Val::Px(16.0),
+                column_gap: Val::Px(8.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(5),
+            ..default()
+        },
+        CornerButtons,
+    ));
+}
+
 fn update_mouse_pos(
     mut mouse_pos: ResMut<MousePos>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
     camera_scale: Res<CameraScale>,
+    focus: Res<CameraFocus>,
 ) {
     let window = q_windows.single();
     if let Some(position) = window.cursor_position() {
-        mouse_pos.0.x = (position.x - window.width() / 2.0) * camera_scale.0;
-        mouse_pos.0.y = -(position.y - window.height() / 2.0) * camera_scale.0;
+        mouse_pos.0.x = (position.x - window.width() / 2.0) * camera_scale.0 + focus.current.0.x;
+        mouse_pos.0.y = -(position.y - window.height() / 2.0) * camera_scale.0 + focus.current.0.y;
     } else {
         mouse_pos.0 = Vec2::new(0.0, 0.0);
     }
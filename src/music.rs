@@ -1,17 +1,14 @@
-use std::time::Duration;
-
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::{AudioSource, *};
+use serde::{Deserialize, Serialize};
 
-use crate::{CLANKS, SPLASHES, SQUELCHES};
+use crate::{storage, CLANKS, SPLASHES, SQUELCHES};
 
 #[derive(Resource)]
 pub struct Music {
     pub dark_figure_hit: Handle<AudioSource>,
     pub thunder: Handle<AudioSource>,
     pub spark: Handle<AudioSource>,
-    pub decay_overlay: Handle<AudioSource>,
-    pub scoring_overlay: Handle<AudioSource>,
 }
 #[derive(Resource)]
 pub struct Clanks(pub Vec<Handle<AudioSource>>);
@@ -20,23 +17,70 @@ pub struct Squelches(pub Vec<Handle<AudioSource>>);
 #[derive(Resource)]
 pub struct Splashes(pub Vec<Handle<AudioSource>>);
 
+/// The build phase loop everything else keeps time with.
+#[derive(Resource)]
+pub struct MusicChannel;
+
+/// The adaptive stems layered over the music.
 #[derive(Resource)]
-struct Muted(bool);
+pub struct OverlayChannel;
 
+/// Clanks, squelches, splashes, thunder and other one-shots.
 #[derive(Resource)]
-pub struct BackgroundMusic(pub Handle<AudioInstance>, pub Option<Handle<AudioInstance>>);
+pub struct EffectsChannel;
+
+/// Channel volumes from 0 to 1, saved whenever they change.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct AudioSettings {
+    pub music: f32,
+    pub overlays: f32,
+    pub effects: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            music: 1.0,
+            overlays: 1.0,
+            effects: 1.0,
+            muted: false,
+        }
+    }
+}
+
+/// The build phase loop. It's started in the same frame as the stems layered over it so they
+/// all begin on the same sample, so `instance` is `None` until those have loaded.
+#[derive(Resource)]
+pub struct BackgroundMusic {
+    pub source: Handle<AudioSource>,
+    pub instance: Option<Handle<AudioInstance>>,
+}
 
 pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Muted(false))
+        app.add_audio_channel::<MusicChannel>()
+            .add_audio_channel::<OverlayChannel>()
+            .add_audio_channel::<EffectsChannel>()
+            .insert_resource(
+                storage::load_json::<AudioSettings>("audio_settings").unwrap_or_default(),
+            )
             .add_systems(Startup, start_load)
-            .add_systems(Update, toggle_mute);
+            .add_systems(
+                Update,
+                (
+                    toggle_mute,
+                    apply_volumes.run_if(resource_changed::<AudioSettings>),
+                    save_settings,
+                )
+                    .chain(),
+            );
     }
 }
 
-fn start_load(mut commands: Commands, assets: ResMut<AssetServer>, audio: Res<Audio>) {
+fn start_load(mut commands: Commands, assets: ResMut<AssetServer>) {
     let mut clanks = Clanks(vec![]);
     for p in &CLANKS {
         clanks.0.push(assets.load::<AudioSource>(*p));
@@ -55,39 +99,49 @@ fn start_load(mut commands: Commands, assets: ResMut<AssetServer>, audio: Res<Au
     }
     commands.insert_resource(splashes);
 
-    let build_phase = assets.load::<AudioSource>("audio/build_phase.ogg");
+    commands.insert_resource(BackgroundMusic {
+        source: assets.load::<AudioSource>("audio/build_phase.ogg"),
+        instance: None,
+    });
     let dark_figure_hit = assets.load::<AudioSource>("audio/dark_figure.ogg");
-    let decay_overlay = assets.load::<AudioSource>("audio/RuinsMakerLooping.ogg");
-    let scoring_overlay = assets.load::<AudioSource>("audio/harp_layer.ogg");
     let thunder = assets.load::<AudioSource>("audio/thunder.ogg");
     let spark = assets.load::<AudioSource>("audio/spark.ogg");
-    let id = audio
-        .play(build_phase.clone())
-        .looped()
-        .fade_in(AudioTween::new(
-            Duration::from_secs(2),
-            AudioEasing::OutPowi(2),
-        ))
-        .handle();
-    commands.insert_resource(BackgroundMusic(id, None));
-
     commands.insert_resource(Music {
         dark_figure_hit,
-        decay_overlay,
-        scoring_overlay,
         thunder,
         spark,
     });
 }
 
-fn toggle_mute(audio: Res<Audio>, keyboard: Res<ButtonInput<KeyCode>>, mut muted: ResMut<Muted>) {
+fn toggle_mute(keyboard: Res<ButtonInput<KeyCode>>, mut settings: ResMut<AudioSettings>) {
     if keyboard.just_pressed(KeyCode::KeyM) {
-        if muted.0 {
-            audio.set_volume(1.0);
-            muted.0 = false;
-        } else {
-            audio.set_volume(0.0);
-            muted.0 = true;
-        }
+        settings.muted = !settings.muted;
+    }
+}
+
+fn apply_volumes(
+    settings: Res<AudioSettings>,
+    music: Res<AudioChannel<MusicChannel>>,
+    overlays: Res<AudioChannel<OverlayChannel>>,
+    effects: Res<AudioChannel<EffectsChannel>>,
+) {
+    let volume = |v: f32| if settings.muted { 0.0 } else { v as f64 };
+    music.set_volume(volume(settings.music));
+    overlays.set_volume(volume(settings.overlays));
+    effects.set_volume(volume(settings.effects));
+}
+
+/// Saves once a slider is let go rather than on every step of the drag.
+fn save_settings(
+    settings: Res<AudioSettings>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut dirty: Local<bool>,
+) {
+    if settings.is_changed() && !settings.is_added() {
+        *dirty = true;
+    }
+    if *dirty && !mouse.pressed(MouseButton::Left) {
+        storage::save_json("audio_settings", &*settings);
+        *dirty = false;
     }
 }
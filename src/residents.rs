@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_mod_picking::prelude::*;
+
+use crate::{
+    block::{BlockTag, BlockTags, WeirdMachine},
+    decay_phase::Decayed,
+    RoundRng,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum ResidentKind {
+    Ghost,
+    Bat,
+    Cultist,
+}
+
+impl ResidentKind {
+    pub const ALL: [ResidentKind; 3] = [ResidentKind::Ghost, ResidentKind::Bat, ResidentKind::Cultist];
+
+    pub fn spookiness(&self) -> u32 {
+        match self {
+            ResidentKind::Ghost => 5,
+            ResidentKind::Bat => 2,
+            ResidentKind::Cultist => 3,
+        }
+    }
+
+    pub fn name(&self, count: usize) -> &'static str {
+        match (self, count) {
+            (ResidentKind::Ghost, 1) => "ghost",
+            (ResidentKind::Ghost, _) => "ghosts",
+            (ResidentKind::Bat, 1) => "bat",
+            (ResidentKind::Bat, _) => "bats",
+            (ResidentKind::Cultist, 1) => "cultist",
+            (ResidentKind::Cultist, _) => "cultists",
+        }
+    }
+}
+
+#[derive(Component, Reflect, Copy, Clone, Debug)]
+#[reflect(Component)]
+pub struct Resident(pub ResidentKind);
+
+/// A ruined block with any of `tags` has `chance` of gaining a resident of `kind`. An empty
+/// tag list matches every ruin.
+pub struct ResidentRule {
+    pub kind: ResidentKind,
+    pub tags: Vec<BlockTag>,
+    pub chance: f32,
+}
+
+#[derive(Resource)]
+pub struct ResidentRules {
+    pub rules: Vec<ResidentRule>,
+    pub capacity: usize,
+}
+
+#[derive(Component)]
+struct ResidentsConsidered;
+
+#[derive(Component)]
+struct Hover {
+    base: Vec3,
+    phase: f32,
+}
+
+#[derive(Resource)]
+struct ResidentAssets(HashMap<ResidentKind, (Handle<Mesh>, Handle<StandardMaterial>)>);
+
+pub struct ResidentPlugin;
+
+impl Plugin for ResidentPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Resident>()
+            .register_type::<ResidentKind>()
+            .insert_resource(ResidentRules {
+                rules: vec![
+                    ResidentRule {
+                        kind: ResidentKind::Ghost,
+                        tags: vec![BlockTag::Crypt, BlockTag::Chapel, BlockTag::Hall],
+                        chance: 0.4,
+                    },
+                    ResidentRule {
+                        kind: ResidentKind::Bat,
+                        tags: vec![BlockTag::Tower, BlockTag::Belfry],
+                        chance: 0.5,
+                    },
+                    ResidentRule {
+                        kind: ResidentKind::Bat,
+                        tags: vec![],
+                        chance: 0.1,
+                    },
+                    ResidentRule {
+                        kind: ResidentKind::Cultist,
+                        tags: vec![BlockTag::Machine, BlockTag::Chapel],
+                        chance: 0.3,
+                    },
+                ],
+                capacity: 3,
+            })
+            .add_systems(Startup, setup)
+            .add_systems(Update, (move_in_residents, animate_residents));
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut assets = HashMap::new();
+    assets.insert(
+        ResidentKind::Ghost,
+        (
+            meshes.add(Capsule3d::new(0.35, 0.5)),
+            materials.add(StandardMaterial {
+                base_color: Color::srgba(0.0, 0.0, 0.0, 0.6),
+                emissive: Color::srgb(2.0, 2.2, 2.4).into(),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
+        ),
+    );
+    assets.insert(
+        ResidentKind::Bat,
+        (
+            meshes.add(Cuboid::new(0.8, 0.15, 0.1)),
+            materials.add(StandardMaterial {
+                base_color: Color::BLACK,
+                emissive: Color::srgb(0.05, 0.02, 0.06).into(),
+                ..default()
+            }),
+        ),
+    );
+    assets.insert(
+        ResidentKind::Cultist,
+        (
+            meshes.add(Cone {
+                radius: 0.35,
+                height: 1.0,
+            }),
+            materials.add(StandardMaterial {
+                base_color: Color::BLACK,
+                emissive: Color::srgb(0.6, 0.05, 0.05).into(),
+                ..default()
+            }),
+        ),
+    );
+    commands.insert_resource(ResidentAssets(assets));
+}
+
+fn move_in_residents(
+    mut commands: Commands,
+    query: Query<
+        (Entity, &Aabb, Option<&BlockTags>, Option<&WeirdMachine>),
+        (With<Decayed>, Without<ResidentsConsidered>),
+    >,
+    rules: Res<ResidentRules>,
+    assets: Res<ResidentAssets>,
+    mut rng: ResMut<RoundRng>,
+) {
+    for (entity, aabb, tags, machine) in &query {
+        commands.entity(entity).insert(ResidentsConsidered);
+        let mut tags = tags.map(|t| t.0.clone()).unwrap_or_default();
+        if machine.is_some() {
+            tags.push(BlockTag::Machine);
+        }
+
+        let mut count = 0;
+        for rule in &rules.rules {
+            if count >= rules.capacity {
+                break;
+            }
+            if !rule.tags.is_empty() && !rule.tags.iter().any(|t| tags.contains(t)) {
+                continue;
+            }
+            if rng.0.f32() >= rule.chance {
+                continue;
+            }
+            let (mesh, material) = assets.0[&rule.kind].clone();
+            let spacing = aabb.half_extents.x * 2.0 / (rules.capacity + 1) as f32;
+            let base = Vec3::new(
+                aabb.center.x - aabb.half_extents.x + spacing * (count + 1) as f32,
+                aabb.center.y + aabb.half_extents.y,
+                aabb.center.z + aabb.half_extents.z + 0.5,
+            );
+            commands.entity(entity).with_children(|parent| {
+                parent.spawn((
+                    PbrBundle {
+                        mesh,
+                        material,
+                        transform: Transform::from_translation(base),
+                        ..default()
+                    },
+                    Resident(rule.kind),
+                    Hover {
+                        base,
+                        phase: fastrand::f32() * std::f32::consts::TAU,
+                    },
+                    Pickable::IGNORE,
+                ));
+            });
+            count += 1;
+        }
+    }
+}
+
+fn animate_residents(mut query: Query<(&mut Transform, &Resident, &Hover)>, time: Res<Time>) {
+    let t = time.elapsed_seconds();
+    for (mut transform, resident, hover) in &mut query {
+        match resident.0 {
+            ResidentKind::Ghost => {
+                transform.translation = hover.base + Vec3::Y * (0.5 + 0.25 * (t * 1.5 + hover.phase).sin());
+            }
+            ResidentKind::Bat => {
+                transform.translation = hover.base
+                    + Vec3::new(
+                        0.6 * (t * 2.0 + hover.phase).cos(),
+                        1.0 + 0.3 * (t * 3.0 + hover.phase).sin(),
+                        0.0,
+                    );
+                transform.scale.x = 0.5 + 0.5 * (t * 20.0 + hover.phase).sin().abs();
+            }
+            ResidentKind::Cultist => {
+                transform.translation = hover.base + Vec3::Y * 0.5;
+                transform.rotation = Quat::from_rotation_z(0.1 * (t * 4.0 + hover.phase).sin());
+            }
+        }
+    }
+}
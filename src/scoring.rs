@@ -0,0 +1,300 @@
+use bevy::{prelude::*, render::primitives::Aabb, utils::HashSet};
+
+use crate::{
+    block::{Block, BlockScoring},
+    block_pool::BlockPoolResident,
+    build_phase::OnTentacle,
+    crow::Perched,
+    decay_phase::{DecayCause, Decayed},
+    residents::Resident,
+    silhouette::Silhouette,
+    villagers::{Fleeing, Villager},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum ScoreCategory {
+    Spookiness,
+    DecayCause,
+    Connectivity,
+    Height,
+    Residents,
+    Cheer,
+    Villagers,
+    Ambience,
+}
+
+impl ScoreCategory {
+    pub const ALL: [ScoreCategory; 8] = [
+        ScoreCategory::Spookiness,
+        ScoreCategory::DecayCause,
+        ScoreCategory::Connectivity,
+        ScoreCategory::Height,
+        ScoreCategory::Residents,
+        ScoreCategory::Cheer,
+        ScoreCategory::Villagers,
+        ScoreCategory::Ambience,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScoreCategory::Spookiness => "Spookiness",
+            ScoreCategory::DecayCause => "Manner of Ruin",
+            ScoreCategory::Connectivity => "Desolation",
+            ScoreCategory::Height => "Looming Heights",
+            ScoreCategory::Residents => "Residents",
+            ScoreCategory::Cheer => "Lingering Cheer",
+            ScoreCategory::Villagers => "Villagers",
+            ScoreCategory::Ambience => "Eldritch Ambience",
+        }
+    }
+}
+
+/// Global weights applied on top of each block's own `BlockScoring`.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct ScoringWeights {
+    pub spookiness: f32,
+    pub lightning: f32,
+    pub debris: f32,
+    /// Points per ruined neighbour touching a ruined block.
+    pub connectivity: f32,
+    /// Points per unit a ruined block sits above the lowest block in the castle.
+    pub height: f32,
+    pub residents: f32,
+    /// Spookiness per crow roosting on a ruined block.
+    pub roosting_crow: f32,
+    pub cheer: f32,
+    pub villager: f32,
+    /// How far apart two ruins' bounds can be and still count as connected.
+    pub connection_slop: f32,
+    /// Points per unit of skyline height change between neighbouring columns.
+    pub jaggedness: f32,
+    /// Points per unit the tallest spire rises above the average skyline.
+    pub prominence: f32,
+    /// Points per unit of open sky between towers or hollow inside them.
+    pub gaps: f32,
+    /// Points for each ruined top block, scaled by the fraction of top blocks that are ruined.
+    pub broken_tops: f32,
+    /// Width of the columns the silhouette is sampled in.
+    pub silhouette_resolution: f32,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            spookiness: 1.0,
+            lightning: 1.0,
+            debris: 2.0,
+            connectivity: 0.5,
+            height: 0.25,
+            residents: 1.0,
+            roosting_crow: 1.0,
+            cheer: 2.0,
+            villager: 5.0,
+            connection_slop: 0.25,
+            jaggedness: 0.5,
+            prominence: 0.5,
+            gaps: 0.25,
+            broken_tops: 3.0,
+            silhouette_resolution: 1.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ScoreEntry {
+    pub entity: Entity,
+    pub category: ScoreCategory,
+    pub points: i32,
+}
+
+/// Every point awarded this round, grouped by block in the order they are tallied.
+#[derive(Resource, Default, Debug)]
+pub struct ScoreBreakdown {
+    pub entries: Vec<ScoreEntry>,
+    pub revealed: usize,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> i32 {
+        self.entries.iter().map(|e| e.points).sum()
+    }
+
+    pub fn category_total(&self, category: ScoreCategory) -> i32 {
+        self.entries
+            .iter()
+            .filter(|e| e.category == category)
+            .map(|e| e.points)
+            .sum()
+    }
+
+    pub fn revealed_category_total(&self, category: ScoreCategory) -> Option<i32> {
+        let mut total = None;
+        for entry in &self.entries[..self.revealed] {
+            if entry.category == category {
+                *total.get_or_insert(0) += entry.points;
+            }
+        }
+        total
+    }
+
+    fn push(&mut self, entity: Entity, category: ScoreCategory, points: f32) {
+        let points = points.round() as i32;
+        if points != 0 {
+            self.entries.push(ScoreEntry {
+                entity,
+                category,
+                points,
+            });
+        }
+    }
+}
+
+fn world_bounds(transform: &GlobalTransform, aabb: Option<&Aabb>) -> (Vec3, Vec3) {
+    if let Some(aabb) = aabb {
+        let (scale, _, _) = transform.to_scale_rotation_translation();
+        (
+            transform.transform_point(aabb.center.into()),
+            (Vec3::from(aabb.half_extents) * scale).abs(),
+        )
+    } else {
+        (transform.translation(), Vec3::splat(1.0))
+    }
+}
+
+pub fn compute_breakdown(
+    mut breakdown: ResMut<ScoreBreakdown>,
+    weights: Res<ScoringWeights>,
+    blocks: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            Option<&Aabb>,
+            Option<&BlockScoring>,
+            Option<&DecayCause>,
+            Option<&Decayed>,
+            Option<&Children>,
+        ),
+        (With<Block>, Without<BlockPoolResident>, Without<OnTentacle>),
+    >,
+    residents: Query<&Resident>,
+    villagers: Query<Entity, (With<Villager>, Without<Fleeing>)>,
+    crows: Query<&Perched>,
+) {
+    *breakdown = ScoreBreakdown::default();
+
+    let ruins: Vec<_> = blocks
+        .iter()
+        .filter(|b| b.5.is_some())
+        .map(|(entity, transform, aabb, ..)| (entity, world_bounds(transform, aabb)))
+        .collect();
+    let floor = blocks
+        .iter()
+        .map(|(_, transform, aabb, ..)| {
+            let (center, half_extents) = world_bounds(transform, aabb);
+            center.y - half_extents.y
+        })
+        .fold(std::f32::INFINITY, f32::min);
+
+    let mut intact = Vec::new();
+    for (entity, transform, aabb, scoring, cause, decayed, children) in &blocks {
+        let scoring = scoring.cloned().unwrap_or_default();
+        if decayed.is_none() {
+            intact.push((entity, scoring.cheer));
+            continue;
+        }
+
+        let roosting = crows.iter().filter(|c| c.block == entity).count();
+        breakdown.push(
+            entity,
+            ScoreCategory::Spookiness,
+            scoring.spookiness * weights.spookiness + roosting as f32 * weights.roosting_crow,
+        );
+
+        let cause_weight = match cause {
+            Some(DecayCause::Lightning) | None => weights.lightning,
+            Some(DecayCause::Debris | DecayCause::Wind) => weights.debris,
+        };
+        breakdown.push(entity, ScoreCategory::DecayCause, cause_weight);
+
+        let (center, half_extents) = world_bounds(transform, aabb);
+        let neighbours = ruins
+            .iter()
+            .filter(|(other, (other_center, other_half_extents))| {
+                let gap = (center - *other_center).abs() - half_extents - *other_half_extents;
+                *other != entity && gap.x < weights.connection_slop && gap.y < weights.connection_slop
+            })
+            .count();
+        breakdown.push(
+            entity,
+            ScoreCategory::Connectivity,
+            neighbours as f32 * weights.connectivity,
+        );
+
+        breakdown.push(
+            entity,
+            ScoreCategory::Height,
+            (center.y - floor).max(0.0) * weights.height,
+        );
+
+        let spookiness: u32 = children
+            .into_iter()
+            .flatten()
+            .filter_map(|child| residents.get(*child).ok())
+            .map(|resident| resident.0.spookiness())
+            .sum();
+        breakdown.push(
+            entity,
+            ScoreCategory::Residents,
+            spookiness as f32 * weights.residents,
+        );
+    }
+
+    for (entity, cheer) in intact {
+        breakdown.push(entity, ScoreCategory::Cheer, -cheer * weights.cheer);
+    }
+    for entity in &villagers {
+        breakdown.push(entity, ScoreCategory::Villagers, -weights.villager);
+    }
+
+    let silhouette = Silhouette::new(
+        blocks
+            .iter()
+            .map(|(entity, transform, aabb, _, _, decayed, _)| {
+                let (center, half_extents) = world_bounds(transform, aabb);
+                (entity, center, half_extents, decayed.is_some())
+            }),
+        weights.silhouette_resolution,
+    );
+    // Skyline points per top block, left to right
+    let mut points: Vec<(Entity, f32)> = Vec::new();
+    let mut award = |entity: Entity, p: f32| match points.iter_mut().find(|(e, _)| *e == entity) {
+        Some((_, total)) => *total += p,
+        None => points.push((entity, p)),
+    };
+    let broken_ratio = silhouette.broken_ratio();
+    let mut broken_tops = HashSet::new();
+    let mut last_top = None;
+    for (i, column) in silhouette.columns.iter().enumerate() {
+        // Open sky is credited to the tower on its left
+        let Some((entity, decayed)) = column.top.or(last_top) else {
+            continue;
+        };
+        let mut column_points = silhouette.gaps(i) * weights.gaps;
+        if column.top.is_some() {
+            column_points += silhouette.jaggedness(i) * weights.jaggedness;
+            // A block topping several columns is only counted as a broken top once
+            if decayed && broken_tops.insert(entity) {
+                column_points += broken_ratio * weights.broken_tops;
+            }
+        }
+        award(entity, column_points);
+        last_top = Some((entity, decayed));
+    }
+    if let Some(tallest) = silhouette.tallest() {
+        award(tallest, silhouette.prominence() * weights.prominence);
+    }
+    for (entity, points) in points {
+        breakdown.push(entity, ScoreCategory::Ambience, points);
+    }
+}
@@ -1,47 +1,81 @@
-use std::time::Duration;
-
 use bevy::{prelude::*, time::Stopwatch};
-use bevy_kira_audio::prelude::*;
 use blenvy::{BlueprintAnimationPlayerLink, BlueprintAnimations, BlueprintInfo};
 
 const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+/// How far the camera leans from the castle's center toward the block being tallied.
+const FOCUS_PULL: f32 = 0.35;
+/// The camera's zoom while following the tally.
+const FOCUS_ZOOM: f32 = 0.85;
 
 use crate::{
     block::Block,
-    decay_phase::{DarkFigureBody, Decayed},
-    music::{BackgroundMusic, Music},
+    decay_phase::DarkFigureBody,
+    high_scores::{record_score, HighScores, RoundRecorded},
     block_pool::BlockPoolResident,
-    GameState,
+    residents::{Resident, ResidentKind},
+    scoring::{compute_breakdown, ScoreBreakdown, ScoreCategory, ScoringWeights},
+    CameraFocus, GameState,
 };
 
 pub struct ScoringPhasePlugin;
 
 #[derive(Component)]
-struct Scored;
+pub struct Scored;
 
 #[derive(Component)]
 struct ScoreText;
 
+#[derive(Component)]
+struct BreakdownText;
+
+#[derive(Component)]
+struct StatsText;
+
+#[derive(Component)]
+struct Highlight;
+
+#[derive(Component)]
+struct PulseMaterial(Handle<StandardMaterial>, LinearRgba);
+
+#[derive(Component)]
+struct ReplayButton;
+
 #[derive(Component)]
 struct UiStuff;
 
 #[derive(Resource)]
-struct TotalScore(u32);
+struct TotalScore(i32);
 
 impl Plugin for ScoringPhasePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(TotalScore(0))
+            .init_resource::<ScoreBreakdown>()
+            .register_type::<ScoringWeights>()
+            .init_resource::<ScoringWeights>()
             .add_systems(Startup, setup)
             .add_systems(
                 Update,
-                (score, update_score_text, button_system).run_if(in_state(GameState::ScoringPhase)),
+                (
+                    score,
+                    update_score_text,
+                    update_breakdown_text,
+                    update_stats_text.after(record_score),
+                    pulse_highlights,
+                    button_system,
+                )
+                    .run_if(in_state(GameState::ScoringPhase)),
             )
+            .add_systems(PostUpdate, restore_highlights)
             .add_systems(
                 OnEnter(GameState::ScoringPhase),
-                (hide_dark_figure, show_text, start_scoring_loop),
+                (
+                    hide_dark_figure,
+                    compute_breakdown.before(show_text),
+                    show_text,
+                ),
             )
-            .add_systems(OnExit(GameState::ScoringPhase), cleanup);
+            .add_systems(OnExit(GameState::ScoringPhase), (reset_camera, cleanup));
     }
 }
 
@@ -62,35 +96,30 @@ fn hide_dark_figure(
         }
     }
 }
-fn start_scoring_loop(
-    mut background: ResMut<BackgroundMusic>,
-    music: Res<Music>,
-    audio: Res<Audio>,
-    mut instances: ResMut<Assets<AudioInstance>>,
-) {
-    if let Some(player) = background.1.as_ref().and_then(|h| instances.get_mut(h)) {
-        player.stop(AudioTween::linear(Duration::from_secs(1)));
-    }
-    if let Some(cur) = audio.state(&background.0).position() {
-        let handle = audio
-            .play(music.scoring_overlay.clone())
-            .start_from(cur)
-            .fade_in(AudioTween::linear(Duration::from_secs(1)))
-            .looped()
-            .handle();
-        background.1 = Some(handle);
-    }
-}
-
-fn show_text(mut commands: Commands) {
+fn show_text(mut commands: Commands, breakdown: Res<ScoreBreakdown>) {
+    let sections = ScoreCategory::ALL
+        .iter()
+        .filter(|category| breakdown.entries.iter().any(|e| e.category == **category))
+        .map(|_| {
+            TextSection::new(
+                "",
+                TextStyle {
+                    font_size: 33.0,
+                    color: Color::srgb(0.5, 0.5, 0.5),
+                    ..default()
+                },
+            )
+        });
     commands
         .spawn((
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.0),
                     height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.0),
                     ..default()
                 },
                 ..default()
@@ -109,23 +138,42 @@ fn show_text(mut commands: Commands) {
                 .with_text_justify(JustifyText::Center),
                 ScoreText,
             ));
+            parent.spawn((
+                TextBundle::from_sections(sections).with_text_justify(JustifyText::Center),
+                BreakdownText,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 25.0,
+                        color: Color::srgb(0.7, 0.7, 0.7),
+                        ..default()
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+                StatsText,
+            ));
             parent
-                .spawn(ButtonBundle {
-                    style: Style {
-                        width: Val::Px(150.0),
-                        height: Val::Px(65.0),
-                        border: UiRect::all(Val::Px(5.0)),
-                        // horizontally center child text
-                        justify_content: JustifyContent::Center,
-                        // vertically center child text
-                        align_items: AlignItems::Center,
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(150.0),
+                            height: Val::Px(65.0),
+                            border: UiRect::all(Val::Px(5.0)),
+                            // horizontally center child text
+                            justify_content: JustifyContent::Center,
+                            // vertically center child text
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        border_color: BorderColor(Color::BLACK),
+                        border_radius: BorderRadius::MAX,
+                        background_color: NORMAL_BUTTON.into(),
                         ..default()
                     },
-                    border_color: BorderColor(Color::BLACK),
-                    border_radius: BorderRadius::MAX,
-                    background_color: NORMAL_BUTTON.into(),
-                    ..default()
-                })
+                    ReplayButton,
+                ))
                 .with_children(|parent| {
                     parent.spawn(TextBundle::from_section(
                         "Replay",
@@ -145,30 +193,182 @@ fn update_score_text(mut query: Query<&mut Text, With<ScoreText>>, score: Res<To
     }
 }
 
+fn update_breakdown_text(
+    mut query: Query<&mut Text, With<BreakdownText>>,
+    breakdown: Res<ScoreBreakdown>,
+    residents: Query<(&Resident, &Parent)>,
+    scored: Query<(), With<Scored>>,
+) {
+    if !breakdown.is_changed() {
+        return;
+    }
+    let current = breakdown
+        .revealed
+        .checked_sub(1)
+        .filter(|i| *i < breakdown.entries.len())
+        .map(|i| breakdown.entries[i].category);
+    let categories = ScoreCategory::ALL
+        .iter()
+        .filter(|category| breakdown.entries.iter().any(|e| e.category == **category));
+    for mut text in &mut query {
+        for (section, category) in text.sections.iter_mut().zip(categories.clone()) {
+            let points = breakdown.revealed_category_total(*category).unwrap_or(0);
+            section.value = if *category == ScoreCategory::Residents {
+                format!(
+                    "{}: {}{}\n",
+                    category.label(),
+                    points,
+                    resident_counts(&residents, &scored)
+                )
+            } else {
+                format!("{}: {}\n", category.label(), points)
+            };
+            section.style.color = if Some(*category) == current {
+                Color::WHITE
+            } else if points == 0 {
+                Color::srgb(0.5, 0.5, 0.5)
+            } else {
+                Color::srgb(0.9, 0.9, 0.9)
+            };
+        }
+    }
+}
+
+/// The residents of the ruins tallied so far, e.g. " (2 ghosts, 1 bat)".
+fn resident_counts(
+    residents: &Query<(&Resident, &Parent)>,
+    scored: &Query<(), With<Scored>>,
+) -> String {
+    let counts: Vec<String> = ResidentKind::ALL
+        .iter()
+        .filter_map(|kind| {
+            let count = residents
+                .iter()
+                .filter(|(r, parent)| r.0 == *kind && scored.contains(parent.get()))
+                .count();
+            (count > 0).then(|| format!("{} {}", count, kind.name(count)))
+        })
+        .collect();
+    if counts.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", counts.join(", "))
+    }
+}
+
+fn update_stats_text(
+    mut query: Query<&mut Text, With<StatsText>>,
+    recorded: Res<RoundRecorded>,
+    high_scores: Res<HighScores>,
+    score: Res<TotalScore>,
+) {
+    if recorded.0.is_none() {
+        return;
+    }
+    for mut text in &mut query {
+        if !text.sections[0].value.is_empty() {
+            continue;
+        }
+        text.sections[0].value = format!(
+            "Best: {}   Average: {:.1}   Rank: {} of {}",
+            high_scores.best().unwrap_or(score.0),
+            high_scores.average(),
+            high_scores.rank(score.0),
+            high_scores.all_scores.len()
+        );
+    }
+}
+
 fn score(
     mut commands: Commands,
-    query: Query<Entity, (With<Decayed>, Without<Scored>)>,
+    mut breakdown: ResMut<ScoreBreakdown>,
     mut stopwatch: Local<Stopwatch>,
     time: Res<Time>,
     mut score: ResMut<TotalScore>,
+    highlighted: Query<Entity, With<Highlight>>,
+    transforms: Query<&GlobalTransform>,
+    mut focus: ResMut<CameraFocus>,
 ) {
     stopwatch.tick(time.delta());
     if stopwatch.elapsed().as_secs_f32() < 0.1 {
         return;
     }
     stopwatch.reset();
-    for entity in &query {
-        commands.entity(entity).insert(Scored);
-        score.0 += 1;
-        return;
+    if let Some(entry) = breakdown.entries.get(breakdown.revealed).copied() {
+        breakdown.revealed += 1;
+        score.0 += entry.points;
+        for entity in &highlighted {
+            if entity != entry.entity {
+                commands.entity(entity).remove::<Highlight>();
+            }
+        }
+        if let Some(mut entity) = commands.get_entity(entry.entity) {
+            entity.insert((Scored, Highlight));
+        }
+        if let Ok(transform) = transforms.get(entry.entity) {
+            focus.target = Some((transform.translation().xy() * FOCUS_PULL, FOCUS_ZOOM));
+        }
+    } else {
+        for entity in &highlighted {
+            commands.entity(entity).remove::<Highlight>();
+        }
+        focus.target = None;
+    }
+}
+
+fn pulse_highlights(
+    mut commands: Commands,
+    highlighted: Query<Entity, With<Highlight>>,
+    children: Query<&Children>,
+    mut handles: Query<(&mut Handle<StandardMaterial>, Option<&PulseMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+) {
+    let pulse = 1.0 + 2.0 * (time.elapsed_seconds() * 10.0).sin().abs();
+    for entity in &highlighted {
+        for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+            if let Ok((mut handle, pulsing)) = handles.get_mut(entity) {
+                if let Some(PulseMaterial(_, emissive)) = pulsing {
+                    if let Some(material) = materials.get_mut(&*handle) {
+                        material.emissive = *emissive * pulse;
+                    }
+                } else if let Some(material) = materials.get(&*handle).cloned() {
+                    let emissive = material.emissive;
+                    let original = std::mem::replace(&mut *handle, materials.add(material));
+                    commands
+                        .entity(entity)
+                        .insert(PulseMaterial(original, emissive));
+                }
+            }
+        }
     }
 }
 
+fn restore_highlights(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Highlight>,
+    children: Query<&Children>,
+    mut handles: Query<(&mut Handle<StandardMaterial>, &PulseMaterial)>,
+) {
+    for entity in removed.read() {
+        for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+            if let Ok((mut handle, pulsing)) = handles.get_mut(entity) {
+                *handle = pulsing.0.clone();
+                commands.entity(entity).remove::<PulseMaterial>();
+            }
+        }
+    }
+}
+
+fn reset_camera(mut focus: ResMut<CameraFocus>) {
+    focus.target = None;
+}
+
 fn button_system(
     mut commands: Commands,
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, &mut BorderColor),
-        (Changed<Interaction>, With<Button>),
+        (Changed<Interaction>, With<ReplayButton>),
     >,
     mut next_state: ResMut<NextState<GameState>>,
     mut score: ResMut<TotalScore>,
@@ -176,7 +376,7 @@ fn button_system(
     for (interaction, mut color, mut border_color) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
-                next_state.set(GameState::BuildPhase);
+                next_state.set(GameState::LevelSelect);
                 score.0 = 0;
             }
             Interaction::Hovered => {
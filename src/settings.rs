@@ -0,0 +1,370 @@
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+
+use crate::{
+    crow::DirectDelivery, crow_jobs::ThievingCrows, music::AudioSettings, spawn_corner_buttons,
+    CornerButtons, CornerPanel,
+};
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+
+type Getter = fn(&AudioSettings) -> f32;
+type Setter = fn(&mut AudioSettings, f32);
+
+/// Every volume the panel has a slider for.
+const SLIDERS: [(&str, Getter, Setter); 3] = [
+    ("Music", |s| s.music, |s, v| s.music = v),
+    ("Overlays", |s| s.overlays, |s, v| s.overlays = v),
+    ("Effects", |s| s.effects, |s, v| s.effects = v),
+];
+
+#[derive(Component)]
+struct SettingsButton;
+
+#[derive(Component)]
+struct SettingsPanel;
+
+#[derive(Component)]
+struct VolumeSlider(usize);
+
+#[derive(Component)]
+struct SliderFill(usize);
+
+#[derive(Component)]
+struct MuteButton;
+
+#[derive(Component)]
+struct ThievingButton;
+
+#[derive(Component)]
+struct DeliveryButton;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup.after(spawn_corner_buttons)).add_systems(
+            Update,
+            (
+                toggle_panel,
+                drag_sliders,
+                mute_button,
+                thieving_button,
+                delivery_button,
+                update_panel.run_if(resource_changed::<AudioSettings>),
+                update_thieving_label.run_if(resource_changed::<ThievingCrows>),
+                update_delivery_label.run_if(resource_changed::<DirectDelivery>),
+                button_colors,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn setup(mut commands: Commands, corner: Query<Entity, With<CornerButtons>>) {
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(Color::BLACK),
+                border_radius: BorderRadius::MAX,
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            SettingsButton,
+        ))
+        .set_parent(corner.single())
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Settings",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::srgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn panel_button() -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        border_color: BorderColor(Color::BLACK),
+        border_radius: BorderRadius::MAX,
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    }
+}
+
+/// Opens and closes with the settings button or Escape.
+fn toggle_panel(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    button: Query<&Interaction, (Changed<Interaction>, With<SettingsButton>)>,
+    panel: Query<Entity, With<SettingsPanel>>,
+    panels: Query<Entity, With<CornerPanel>>,
+    settings: Res<AudioSettings>,
+    thieving: Res<ThievingCrows>,
+    direct_delivery: Res<DirectDelivery>,
+) {
+    let pressed = button.iter().any(|i| *i == Interaction::Pressed);
+    if !pressed && !keyboard.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if let Ok(panel) = panel.get_single() {
+        commands.entity(panel).despawn_recursive();
+        return;
+    }
+    for panel in &panels {
+        commands.entity(panel).despawn_recursive();
+    }
+
+    let text_style = TextStyle {
+        font_size: 18.0,
+        color: Color::srgb(0.9, 0.9, 0.9),
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(16.0),
+                    bottom: Val::Px(56.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            SettingsPanel,
+            CornerPanel,
+        ))
+        .with_children(|parent| {
+            for (i, (label, get, _)) in SLIDERS.iter().enumerate() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(12.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(
+                            TextBundle::from_section(*label, text_style.clone()).with_style(
+                                Style {
+                                    width: Val::Px(80.0),
+                                    ..default()
+                                },
+                            ),
+                        );
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(200.0),
+                                        height: Val::Px(14.0),
+                                        ..default()
+                                    },
+                                    background_color: Color::srgb(0.2, 0.2, 0.2).into(),
+                                    ..default()
+                                },
+                                RelativeCursorPosition::default(),
+                                VolumeSlider(i),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    NodeBundle {
+                                        style: Style {
+                                            width: Val::Percent(get(&settings) * 100.0),
+                                            height: Val::Percent(100.0),
+                                            ..default()
+                                        },
+                                        background_color: Color::srgb(0.7, 0.65, 0.5).into(),
+                                        ..default()
+                                    },
+                                    SliderFill(i),
+                                ));
+                            });
+                    });
+            }
+            parent
+                .spawn((panel_button(), MuteButton))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        mute_label(&settings),
+                        text_style.clone(),
+                    ));
+                });
+            parent
+                .spawn((panel_button(), ThievingButton))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        thieving_label(&thieving),
+                        text_style.clone(),
+                    ));
+                });
+            parent
+                .spawn((panel_button(), DeliveryButton))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        delivery_label(&direct_delivery),
+                        text_style.clone(),
+                    ));
+                });
+        });
+}
+
+fn mute_label(settings: &AudioSettings) -> &'static str {
+    if settings.muted {
+        "Unmute (M)"
+    } else {
+        "Mute (M)"
+    }
+}
+
+fn thieving_label(thieving: &ThievingCrows) -> &'static str {
+    if thieving.0 {
+        "Thieving crows: on (T)"
+    } else {
+        "Thieving crows: off (T)"
+    }
+}
+
+fn delivery_label(direct_delivery: &DirectDelivery) -> &'static str {
+    if direct_delivery.0 {
+        "Crows place decorations (D)"
+    } else {
+        "Crows hand over decorations (D)"
+    }
+}
+
+fn set_label(children: &Children, texts: &mut Query<&mut Text>, label: &str) {
+    for child in children {
+        if let Ok(mut text) = texts.get_mut(*child) {
+            text.sections[0].value = label.to_string();
+        }
+    }
+}
+
+fn drag_sliders(
+    sliders: Query<(&Interaction, &RelativeCursorPosition, &VolumeSlider)>,
+    mut settings: ResMut<AudioSettings>,
+) {
+    for (interaction, cursor, slider) in &sliders {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        let (_, get, set) = SLIDERS[slider.0];
+        let value = position.x.clamp(0.0, 1.0);
+        if get(&settings) != value {
+            set(&mut settings, value);
+        }
+    }
+}
+
+fn mute_button(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<MuteButton>)>,
+    mut settings: ResMut<AudioSettings>,
+) {
+    if buttons.iter().any(|i| *i == Interaction::Pressed) {
+        settings.muted = !settings.muted;
+    }
+}
+
+fn thieving_button(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<ThievingButton>)>,
+    mut thieving: ResMut<ThievingCrows>,
+) {
+    if buttons.iter().any(|i| *i == Interaction::Pressed) {
+        thieving.0 = !thieving.0;
+    }
+}
+
+fn delivery_button(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<DeliveryButton>)>,
+    mut direct_delivery: ResMut<DirectDelivery>,
+) {
+    if buttons.iter().any(|i| *i == Interaction::Pressed) {
+        direct_delivery.0 = !direct_delivery.0;
+    }
+}
+
+fn update_panel(
+    settings: Res<AudioSettings>,
+    mut fills: Query<(&mut Style, &SliderFill)>,
+    mute: Query<&Children, With<MuteButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (mut style, fill) in &mut fills {
+        style.width = Val::Percent(SLIDERS[fill.0].1(&settings) * 100.0);
+    }
+    for children in &mute {
+        set_label(children, &mut texts, mute_label(&settings));
+    }
+}
+
+fn update_thieving_label(
+    thieving: Res<ThievingCrows>,
+    button: Query<&Children, With<ThievingButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    for children in &button {
+        set_label(children, &mut texts, thieving_label(&thieving));
+    }
+}
+
+fn update_delivery_label(
+    direct_delivery: Res<DirectDelivery>,
+    button: Query<&Children, With<DeliveryButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    for children in &button {
+        set_label(children, &mut texts, delivery_label(&direct_delivery));
+    }
+}
+
+fn button_colors(
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (
+            Changed<Interaction>,
+            Or<(
+                With<SettingsButton>,
+                With<MuteButton>,
+                With<ThievingButton>,
+                With<DeliveryButton>,
+            )>,
+        ),
+    >,
+) {
+    for (interaction, mut color, mut border_color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed | Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+                border_color.0 = Color::WHITE;
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+                border_color.0 = Color::BLACK;
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+
+/// One vertical slice of the castle as seen from the front.
+#[derive(Clone, Debug, Default)]
+pub struct Column {
+    /// The block whose top is highest in this slice, and whether it is ruined.
+    pub top: Option<(Entity, bool)>,
+    pub height: f32,
+    /// Height below `height` that no block covers.
+    pub hollow: f32,
+}
+
+/// The castle's outline against the sky, sampled in columns `resolution` units wide.
+#[derive(Clone, Debug, Default)]
+pub struct Silhouette {
+    pub columns: Vec<Column>,
+}
+
+impl Silhouette {
+    /// `blocks` yields each block's world space center and half extents, and whether it is
+    /// ruined.
+    pub fn new(blocks: impl Iterator<Item = (Entity, Vec3, Vec3, bool)>, resolution: f32) -> Self {
+        let blocks: Vec<_> = blocks.collect();
+        if blocks.is_empty() {
+            return Self::default();
+        }
+        let left = blocks
+            .iter()
+            .map(|(_, c, h, _)| c.x - h.x)
+            .fold(f32::INFINITY, f32::min);
+        let right = blocks
+            .iter()
+            .map(|(_, c, h, _)| c.x + h.x)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let floor = blocks
+            .iter()
+            .map(|(_, c, h, _)| c.y - h.y)
+            .fold(f32::INFINITY, f32::min);
+
+        let count = ((right - left) / resolution).ceil().max(1.0) as usize;
+        let columns = (0..count)
+            .map(|i| {
+                let x = left + (i as f32 + 0.5) * resolution;
+                let mut spans: Vec<_> = blocks
+                    .iter()
+                    .filter(|(_, c, h, _)| (x - c.x).abs() <= h.x)
+                    .map(|(e, c, h, decayed)| (c.y - h.y - floor, c.y + h.y - floor, *e, *decayed))
+                    .collect();
+                if spans.is_empty() {
+                    return Column::default();
+                }
+                spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let mut covered = 0.0;
+                let mut reach = 0.0f32;
+                let mut top = None;
+                for (bottom, top_y, entity, decayed) in spans {
+                    covered += (top_y - bottom.max(reach)).max(0.0);
+                    if top_y >= reach {
+                        top = Some((entity, decayed));
+                    }
+                    reach = reach.max(top_y);
+                }
+                Column {
+                    top,
+                    height: reach,
+                    hollow: reach - covered,
+                }
+            })
+            .collect();
+
+        Self { columns }
+    }
+
+    /// How sharply the skyline changes between neighbouring columns, split evenly between them.
+    pub fn jaggedness(&self, i: usize) -> f32 {
+        let h = self.columns[i].height;
+        let left = i
+            .checked_sub(1)
+            .map(|j| (self.columns[j].height - h).abs())
+            .unwrap_or(0.0);
+        let right = self
+            .columns
+            .get(i + 1)
+            .map(|c| (c.height - h).abs())
+            .unwrap_or(0.0);
+        (left + right) * 0.5
+    }
+
+    /// How far the tallest spire looms over the rest of the skyline.
+    pub fn prominence(&self) -> f32 {
+        if self.columns.is_empty() {
+            return 0.0;
+        }
+        let max = self.columns.iter().map(|c| c.height).fold(0.0, f32::max);
+        let mean = self.columns.iter().map(|c| c.height).sum::<f32>() / self.columns.len() as f32;
+        max - mean
+    }
+
+    /// Open sky between towers plus hollows inside them.
+    pub fn gaps(&self, i: usize) -> f32 {
+        let column = &self.columns[i];
+        if column.top.is_some() {
+            column.hollow
+        } else {
+            // An empty column only counts as a gap if it is flanked by towers.
+            let left = self.columns[..i]
+                .iter()
+                .map(|c| c.height)
+                .fold(0.0, f32::max);
+            let right = self.columns[i + 1..]
+                .iter()
+                .map(|c| c.height)
+                .fold(0.0, f32::max);
+            left.min(right)
+        }
+    }
+
+    /// Fraction of the skyline's top blocks that are ruined.
+    pub fn broken_ratio(&self) -> f32 {
+        // A wide block tops several columns but is still only one block
+        let mut tops: Vec<(Entity, bool)> = Vec::new();
+        for top in self.columns.iter().filter_map(|c| c.top) {
+            if !tops.iter().any(|(e, _)| *e == top.0) {
+                tops.push(top);
+            }
+        }
+        if tops.is_empty() {
+            return 0.0;
+        }
+        tops.iter().filter(|(_, decayed)| *decayed).count() as f32 / tops.len() as f32
+    }
+
+    pub fn tallest(&self) -> Option<Entity> {
+        self.columns
+            .iter()
+            .filter_map(|c| c.top.map(|(e, _)| (e, c.height)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(e, _)| e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: u32, x: (f32, f32), y: (f32, f32), decayed: bool) -> (Entity, Vec3, Vec3, bool) {
+        let center = Vec3::new((x.0 + x.1) / 2.0, (y.0 + y.1) / 2.0, 0.0);
+        let half_extents = Vec3::new((x.1 - x.0) / 2.0, (y.1 - y.0) / 2.0, 0.5);
+        (Entity::from_raw(id), center, half_extents, decayed)
+    }
+
+    #[test]
+    fn two_towers_with_a_gap() {
+        let silhouette = Silhouette::new(
+            [
+                block(1, (0.0, 1.0), (0.0, 2.0), false),
+                block(2, (2.0, 3.0), (0.0, 1.0), true),
+            ]
+            .into_iter(),
+            1.0,
+        );
+        assert_eq!(silhouette.columns.len(), 3);
+        assert!(silhouette.columns[1].top.is_none());
+
+        assert_eq!(silhouette.jaggedness(0), 1.0);
+        assert_eq!(silhouette.jaggedness(2), 0.5);
+        // The empty column is as deep as the shorter tower beside it
+        assert_eq!(silhouette.gaps(1), 1.0);
+        assert_eq!(silhouette.gaps(0), 0.0);
+        assert_eq!(silhouette.broken_ratio(), 0.5);
+        assert_eq!(silhouette.prominence(), 1.0);
+        assert_eq!(silhouette.tallest(), Some(Entity::from_raw(1)));
+    }
+
+    #[test]
+    fn hollow_under_an_overhang() {
+        let silhouette = Silhouette::new(
+            [
+                block(1, (0.0, 1.0), (0.0, 1.0), false),
+                block(2, (0.0, 1.0), (2.5, 3.5), false),
+            ]
+            .into_iter(),
+            1.0,
+        );
+        assert_eq!(silhouette.columns.len(), 1);
+        assert_eq!(silhouette.columns[0].top, Some((Entity::from_raw(2), false)));
+        assert_eq!(silhouette.columns[0].height, 3.5);
+        assert_eq!(silhouette.gaps(0), 1.5);
+    }
+
+    #[test]
+    fn wide_ruin_counts_once() {
+        let silhouette = Silhouette::new(
+            [
+                block(1, (0.0, 2.0), (0.0, 1.0), true),
+                block(2, (2.0, 3.0), (0.0, 1.0), false),
+            ]
+            .into_iter(),
+            1.0,
+        );
+        assert_eq!(silhouette.columns.len(), 3);
+        assert_eq!(silhouette.broken_ratio(), 0.5);
+    }
+
+    #[test]
+    fn empty_castle() {
+        let silhouette = Silhouette::new(std::iter::empty(), 1.0);
+        assert!(silhouette.columns.is_empty());
+        assert_eq!(silhouette.broken_ratio(), 0.0);
+        assert_eq!(silhouette.prominence(), 0.0);
+        assert_eq!(silhouette.tallest(), None);
+    }
+}
@@ -0,0 +1,314 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    reflect::TypePath,
+    render::render_resource::{AsBindGroup, ShaderRef},
+};
+
+use crate::{
+    achievements::{DisasterResolved, LightningChain, RoundScored},
+    decay_phase::Eye,
+    environmental_decoration::{Sky, Star, TimeOfDay},
+    levels::CurrentLevel,
+    GameState, RoundSeed,
+};
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct SkyMaterial {
+    #[uniform(100)]
+    zenith: Vec4,
+    #[uniform(100)]
+    horizon: Vec4,
+    #[uniform(100)]
+    moon: Vec4,
+    #[uniform(100)]
+    moon_color: Vec4,
+    #[uniform(100)]
+    params: Vec4,
+}
+
+impl Material for SkyMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/sky.wgsl".into()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SkyEvent {
+    /// The moon bleeds for the rest of the round.
+    BloodMoon,
+    /// The stars leave their places to draw a sign, then drift back.
+    Sign,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum SkyTrigger {
+    /// A single strike ruins at least this many blocks.
+    Decayed(usize),
+    /// A single strike arcs through at least this many conductors.
+    Chain(usize),
+    /// The round scores at least this much.
+    Score(i32),
+}
+
+const SKY_EVENTS: [(SkyTrigger, SkyEvent); 3] = [
+    (SkyTrigger::Decayed(12), SkyEvent::BloodMoon),
+    (SkyTrigger::Chain(8), SkyEvent::Sign),
+    (SkyTrigger::Score(60), SkyEvent::Sign),
+];
+
+const SIGN_DURATION: Duration = Duration::from_secs(8);
+const SIGN_CENTER: Vec2 = Vec2::new(0.0, 27.0);
+const SIGN_RADIUS: f32 = 9.0;
+const MOON_RADIUS: f32 = 2.5;
+const HORIZON_HEIGHT: f32 = -5.0;
+const ZENITH_HEIGHT: f32 = 35.0;
+
+/// Where the sky is heading, eased towards every frame.
+#[derive(Resource)]
+struct SkyState {
+    horizon: LinearRgba,
+    moon_position: Vec2,
+    moon_visibility: f32,
+    moon_phase: f32,
+    blood: f32,
+    flash: f32,
+}
+
+#[derive(Resource, Default)]
+struct ActiveSkyEvents {
+    blood_moon: bool,
+    sign: Option<Timer>,
+}
+
+/// Where a star sits when it isn't part of a sign.
+#[derive(Component)]
+struct StarHome(Vec3);
+
+pub struct SkyPlugin;
+
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<SkyMaterial>::default())
+            .insert_resource(SkyState {
+                horizon: horizon_color(TimeOfDay::Day).to_linear(),
+                moon_position: moon_position(TimeOfDay::Day),
+                moon_visibility: 0.0,
+                moon_phase: 0.5,
+                blood: 0.0,
+                flash: 0.0,
+            })
+            .init_resource::<ActiveSkyEvents>()
+            .add_systems(
+                Update,
+                (
+                    replace_sky_material,
+                    choose_moon_phase.run_if(resource_changed::<RoundSeed>),
+                    trigger_sky_events,
+                    animate_sky,
+                    arrange_stars,
+                )
+                    .chain(),
+            )
+            .add_systems(OnEnter(GameState::BuildPhase), clear_sky_events);
+    }
+}
+
+fn horizon_color(time_of_day: TimeOfDay) -> Color {
+    match time_of_day {
+        TimeOfDay::Day => Color::srgb(0.62, 0.58, 0.6),
+        TimeOfDay::Dusk => Color::srgb(0.85, 0.45, 0.3),
+        TimeOfDay::Night => Color::srgb(0.12, 0.11, 0.16),
+        TimeOfDay::WitchingHour => Color::srgb(0.15, 0.3, 0.12),
+        TimeOfDay::Dawn => Color::srgb(0.9, 0.6, 0.55),
+    }
+}
+
+/// The moon rises at dusk, peaks at the witching hour and sets at dawn.
+fn moon_position(time_of_day: TimeOfDay) -> Vec2 {
+    match time_of_day {
+        TimeOfDay::Day => Vec2::new(32.0, 6.0),
+        TimeOfDay::Dusk => Vec2::new(-28.0, 14.0),
+        TimeOfDay::Night => Vec2::new(-10.0, 26.0),
+        TimeOfDay::WitchingHour => Vec2::new(6.0, 30.0),
+        TimeOfDay::Dawn => Vec2::new(26.0, 16.0),
+    }
+}
+
+fn moon_visibility(time_of_day: TimeOfDay) -> f32 {
+    match time_of_day {
+        TimeOfDay::Day => 0.0,
+        TimeOfDay::Dusk => 0.6,
+        TimeOfDay::Night | TimeOfDay::WitchingHour => 1.0,
+        TimeOfDay::Dawn => 0.4,
+    }
+}
+
+fn replace_sky_material(
+    mut commands: Commands,
+    query: Query<Entity, (With<Sky>, Without<Star>, With<Handle<StandardMaterial>>)>,
+    mut materials: ResMut<Assets<SkyMaterial>>,
+) {
+    for entity in &query {
+        commands
+            .entity(entity)
+            .remove::<Handle<StandardMaterial>>()
+            .insert(materials.add(SkyMaterial::default()));
+    }
+}
+
+fn choose_moon_phase(seed: Res<RoundSeed>, mut state: ResMut<SkyState>) {
+    state.moon_phase = fastrand::Rng::with_seed(seed.0 ^ 0x6d6f6f6e).f32();
+}
+
+fn clear_sky_events(mut events: ResMut<ActiveSkyEvents>) {
+    *events = ActiveSkyEvents::default();
+}
+
+fn trigger_sky_events(
+    mut resolved: EventReader<DisasterResolved>,
+    mut chains: EventReader<LightningChain>,
+    mut scored: EventReader<RoundScored>,
+    mut events: ResMut<ActiveSkyEvents>,
+    mut state: ResMut<SkyState>,
+) {
+    let mut fired = vec![];
+    for event in resolved.read() {
+        state.flash = 1.0;
+        fired.extend(SKY_EVENTS.iter().filter_map(|(trigger, sky_event)| {
+            matches!(trigger, SkyTrigger::Decayed(n) if event.decayed >= *n).then_some(*sky_event)
+        }));
+    }
+    for event in chains.read() {
+        fired.extend(SKY_EVENTS.iter().filter_map(|(trigger, sky_event)| {
+            matches!(trigger, SkyTrigger::Chain(n) if event.length >= *n).then_some(*sky_event)
+        }));
+    }
+    for event in scored.read() {
+        fired.extend(SKY_EVENTS.iter().filter_map(|(trigger, sky_event)| {
+            matches!(trigger, SkyTrigger::Score(n) if event.score >= *n).then_some(*sky_event)
+        }));
+    }
+    for sky_event in fired {
+        match sky_event {
+            SkyEvent::BloodMoon => events.blood_moon = true,
+            SkyEvent::Sign => {
+                if events.sign.is_none() {
+                    events.sign = Some(Timer::new(SIGN_DURATION, TimerMode::Once));
+                }
+            }
+        }
+    }
+}
+
+fn animate_sky(
+    mut query: Query<(&mut Sky, &Handle<SkyMaterial>)>,
+    mut materials: ResMut<Assets<SkyMaterial>>,
+    mut state: ResMut<SkyState>,
+    events: Res<ActiveSkyEvents>,
+    time_of_day: Res<TimeOfDay>,
+    level: Res<CurrentLevel>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    let ease = (dt * 1.5).min(1.0);
+    let blood_target = if events.blood_moon { 1.0 } else { 0.0 };
+    let horizon = level
+        .sky(*time_of_day)
+        .map_or(horizon_color(*time_of_day), |colors| {
+            Color::srgb_from_array(colors.horizon)
+        })
+        .mix(&Color::srgb(0.5, 0.05, 0.04), blood_target * 0.5)
+        .to_linear();
+    state.horizon = state.horizon.mix(&horizon, ease);
+    state.moon_position = state
+        .moon_position
+        .lerp(moon_position(*time_of_day), (dt * 0.5).min(1.0));
+    state.moon_visibility += (moon_visibility(*time_of_day) - state.moon_visibility) * ease;
+    state.blood += (blood_target - state.blood) * ease;
+    state.flash = (state.flash - dt * 1.5).max(0.0);
+
+    // The sky stutters after a strike rather than fading smoothly
+    let now = time.elapsed();
+    let flicker = 0.5 + 0.5 * (now.as_secs_f32() * 47.0).sin().abs();
+    let moon_color = Color::srgb(1.0, 0.97, 0.85)
+        .mix(&Color::srgb(0.8, 0.1, 0.05), state.blood)
+        .to_linear();
+
+    for (mut sky, handle) in &mut query {
+        if let Sky::Transition {
+            end_color,
+            end_time,
+            end_star_brightness,
+            ..
+        } = &*sky
+        {
+            if *end_time <= now {
+                *sky = Sky::Color(*end_color, *end_star_brightness);
+            }
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            material.zenith = sky.current_color(now).to_linear().to_vec4();
+            material.horizon = state.horizon.to_vec4();
+            material.moon = state
+                .moon_position
+                .extend(MOON_RADIUS)
+                .extend(state.moon_phase);
+            material.moon_color = moon_color.with_alpha(state.moon_visibility).to_vec4();
+            material.params = Vec4::new(
+                state.flash * state.flash * flicker,
+                HORIZON_HEIGHT,
+                ZENITH_HEIGHT,
+                0.0,
+            );
+        }
+    }
+}
+
+/// A point `f` of the way around a pentagram drawn in one stroke.
+fn sign_point(f: f32) -> Vec2 {
+    let vertex = |k: usize| {
+        let angle = (k * 2 % 5) as f32 * std::f32::consts::TAU / 5.0;
+        SIGN_CENTER + Vec2::new(angle.sin(), angle.cos()) * SIGN_RADIUS
+    };
+    let s = f.fract() * 5.0;
+    let edge = s.floor() as usize;
+    vertex(edge).lerp(vertex(edge + 1), s.fract())
+}
+
+fn arrange_stars(
+    mut commands: Commands,
+    mut stars: Query<(Entity, &mut Transform, Option<&StarHome>), (With<Star>, Without<Eye>)>,
+    mut events: ResMut<ActiveSkyEvents>,
+    time: Res<Time>,
+) {
+    let forming = match &mut events.sign {
+        Some(timer) => !timer.tick(time.delta()).finished(),
+        None => false,
+    };
+    if !forming {
+        events.sign = None;
+    }
+
+    let mut stars: Vec<_> = stars.iter_mut().collect();
+    stars.sort_by_key(|(entity, ..)| *entity);
+    let count = stars.len();
+    let ease = (time.delta_seconds() * 2.0).min(1.0);
+    for (i, (entity, transform, home)) in stars.iter_mut().enumerate() {
+        let Some(home) = home else {
+            commands
+                .entity(*entity)
+                .insert(StarHome(transform.translation));
+            continue;
+        };
+        let target = if forming {
+            sign_point(i as f32 / count as f32).extend(home.0.z)
+        } else {
+            home.0
+        };
+        if transform.translation.distance_squared(target) > 1e-4 {
+            transform.translation = transform.translation.lerp(target, ease);
+        }
+    }
+}
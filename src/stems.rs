@@ -0,0 +1,257 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+};
+use bevy_kira_audio::prelude::{AudioSource, *};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::Block,
+    block_pool::{BlockPoolResident, TempBlockPoolResident},
+    build_phase::OnTentacle,
+    decay_phase::{Decayed, Lightning},
+    environmental_decoration::TimeOfDay,
+    music::{BackgroundMusic, MusicChannel, OverlayChannel},
+    GameState,
+};
+
+const STEMS_PATH: &str = "music.stems.json";
+
+/// What the game tells the music, each running from 0 to 1.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StemInput {
+    /// How tall the castle stands within `height_range`.
+    Height,
+    /// How many blocks are ruined, out of `decayed_full`.
+    Decayed,
+    /// A lightning strike is being aimed.
+    Aiming,
+    Night,
+    DecayPhase,
+    ScoringPhase,
+}
+
+/// A layer looping alongside the background music, and exactly as long as it. Its volume is
+/// `base` plus the sum of each input times its weight, and follows that target at `response`
+/// per second.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Stem {
+    pub path: String,
+    #[serde(default)]
+    pub base: f32,
+    pub weights: HashMap<StemInput, f32>,
+    pub response: f32,
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug)]
+pub struct StemSet {
+    pub height_range: [f32; 2],
+    pub decayed_full: f32,
+    pub stems: Vec<Stem>,
+}
+
+#[derive(Default)]
+struct StemSetLoader;
+
+impl AssetLoader for StemSetLoader {
+    type Asset = StemSet;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["stems.json"]
+    }
+}
+
+struct PlayingStem {
+    instance: Handle<AudioInstance>,
+    volume: f32,
+}
+
+/// The stem set and, once every stem has loaded, the instances playing it.
+#[derive(Resource)]
+struct Stems {
+    handle: Handle<StemSet>,
+    sources: Vec<Handle<AudioSource>>,
+    playing: Vec<PlayingStem>,
+}
+
+pub struct StemsPlugin;
+
+impl Plugin for StemsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<StemSet>()
+            .init_asset_loader::<StemSetLoader>()
+            .add_systems(Startup, load)
+            .add_systems(
+                Update,
+                (reload_stems, start_stems, mix_stems).chain(),
+            );
+    }
+}
+
+fn load(mut commands: Commands, assets: Res<AssetServer>) {
+    commands.insert_resource(Stems {
+        handle: assets.load(STEMS_PATH),
+        sources: vec![],
+        playing: vec![],
+    });
+}
+
+/// Stops everything when the stem file changes on disk, so the background and the new stems
+/// start over together.
+fn reload_stems(
+    mut events: EventReader<AssetEvent<StemSet>>,
+    mut stems: ResMut<Stems>,
+    mut background: ResMut<BackgroundMusic>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    assets: Res<AssetServer>,
+    sets: Res<Assets<StemSet>>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if *id != stems.handle.id() {
+            continue;
+        }
+        let playing = stems.playing.drain(..).map(|stem| stem.instance);
+        for handle in playing.chain(background.instance.take()) {
+            if let Some(instance) = instances.get_mut(&handle) {
+                instance.stop(AudioTween::default());
+            }
+        }
+        stems.sources = sets
+            .get(*id)
+            .map(|set| set.stems.iter().map(|s| assets.load(&s.path)).collect())
+            .unwrap_or_default();
+    }
+}
+
+/// Once the background loop and every stem have loaded, starts them all from the top in the
+/// same frame, stems silent. Kira begins sounds queued together on the same sample and they
+/// loop at the same length, so from then on only their volumes change. Without a stem file
+/// the background plays alone.
+fn start_stems(
+    mut stems: ResMut<Stems>,
+    mut background: ResMut<BackgroundMusic>,
+    music: Res<AudioChannel<MusicChannel>>,
+    overlays: Res<AudioChannel<OverlayChannel>>,
+    assets: Res<AssetServer>,
+    sets: Res<Assets<StemSet>>,
+) {
+    let stems_settled = match sets.get(&stems.handle) {
+        Some(set) => {
+            stems.sources.len() == set.stems.len()
+                && stems
+                    .sources
+                    .iter()
+                    .all(|s| assets.is_loaded_with_dependencies(s))
+        }
+        None => matches!(assets.load_state(&stems.handle), LoadState::Failed(_)),
+    };
+    if background.instance.is_some()
+        || !stems_settled
+        || !assets.is_loaded_with_dependencies(&background.source)
+    {
+        return;
+    }
+    background.instance = Some(
+        music
+            .play(background.source.clone())
+            .looped()
+            .fade_in(AudioTween::new(
+                Duration::from_secs(2),
+                AudioEasing::OutPowi(2),
+            ))
+            .handle(),
+    );
+    stems.playing = stems
+        .sources
+        .iter()
+        .map(|source| PlayingStem {
+            instance: overlays
+                .play(source.clone())
+                .with_volume(0.0)
+                .looped()
+                .handle(),
+            volume: 0.0,
+        })
+        .collect();
+}
+
+fn mix_stems(
+    mut stems: ResMut<Stems>,
+    sets: Res<Assets<StemSet>>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    castle: Query<
+        &GlobalTransform,
+        (
+            With<Block>,
+            Without<BlockPoolResident>,
+            Without<TempBlockPoolResident>,
+            Without<OnTentacle>,
+        ),
+    >,
+    decayed: Query<(), With<Decayed>>,
+    aiming: Query<(), With<Lightning>>,
+    time_of_day: Res<TimeOfDay>,
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+) {
+    let Some(set) = sets.get(&stems.handle) else {
+        return;
+    };
+    let height = castle
+        .iter()
+        .map(|t| t.translation().y)
+        .fold(set.height_range[0], f32::max);
+    let flag = |on: bool| if on { 1.0 } else { 0.0 };
+    let input = |input: StemInput| match input {
+        StemInput::Height => ((height - set.height_range[0])
+            / (set.height_range[1] - set.height_range[0]))
+            .clamp(0.0, 1.0),
+        StemInput::Decayed => (decayed.iter().count() as f32 / set.decayed_full).min(1.0),
+        StemInput::Aiming => flag(!aiming.is_empty()),
+        StemInput::Night => flag(time_of_day.is_dark()),
+        StemInput::DecayPhase => flag(*state.get() == GameState::DecayPhase),
+        StemInput::ScoringPhase => flag(*state.get() == GameState::ScoringPhase),
+    };
+
+    let dt = time.delta_seconds();
+    for (stem, playing) in set.stems.iter().zip(stems.playing.iter_mut()) {
+        let target = (stem.base
+            + stem
+                .weights
+                .iter()
+                .map(|(i, weight)| input(*i) * weight)
+                .sum::<f32>())
+        .clamp(0.0, 1.0);
+        let mut volume = playing.volume + (target - playing.volume) * (stem.response * dt).min(1.0);
+        if (target - volume).abs() < 0.001 {
+            volume = target;
+        }
+        if volume == playing.volume {
+            continue;
+        }
+        playing.volume = volume;
+        if let Some(instance) = instances.get_mut(&playing.instance) {
+            instance.set_volume(volume as f64, AudioTween::default());
+        }
+    }
+}
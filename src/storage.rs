@@ -0,0 +1,101 @@
+//! Small key/value persistence: a file per key on native, `localStorage` on the web.
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(key: &str) -> std::path::PathBuf {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".local/share"))
+        })
+        .unwrap_or_else(|| std::path::PathBuf::from("."));
+    base.join("spooky_jam").join(format!("{key}.json"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) {
+    let path = path(key);
+    if let Some(dir) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            log::warn!("Could not create {}: {e}", dir.display());
+            return;
+        }
+    }
+    if let Err(e) = std::fs::write(&path, value) {
+        log::warn!("Could not write {}: {e}", path.display());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("spooky_jam.{key}"))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, value: &str) {
+    if let Some(storage) = local_storage() {
+        if storage
+            .set_item(&format!("spooky_jam.{key}"), value)
+            .is_err()
+        {
+            log::warn!("Could not write {key} to localStorage");
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+pub fn load_json<T: serde::de::DeserializeOwned>(key: &str) -> Option<T> {
+    let value = load(key)?;
+    match serde_json::from_str(&value) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            log::warn!("Ignoring unreadable {key}: {e}");
+            None
+        }
+    }
+}
+
+pub fn save_json<T: serde::Serialize>(key: &str, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(value) => save(key, &value),
+        Err(e) => log::warn!("Could not serialize {key}: {e}"),
+    }
+}
+
+/// Formats seconds since the unix epoch as `YYYY-MM-DD`.
+pub fn format_date(secs: u64) -> String {
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,164 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_mod_picking::prelude::*;
+
+use crate::{
+    block::{AnchorColor, Anchors},
+    build_phase::NeedsClearance,
+    decay_phase::{Decayed, ReplacedBy},
+    GameState, RoundRng,
+};
+
+#[derive(Component, Reflect, Copy, Clone, Debug)]
+#[reflect(Component)]
+pub struct Villager {
+    pub morale: f32,
+}
+
+#[derive(Component)]
+pub struct Fleeing(f32);
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct VillagerParams {
+    pub chance: f32,
+    /// Morale lost when the block a villager lives in is ruined.
+    pub host_loss: f32,
+    /// Morale lost by villagers right next to a ruined block, falling off to nothing at
+    /// `nearby_radius`.
+    pub nearby_loss: f32,
+    pub nearby_radius: f32,
+    pub flee_speed: f32,
+}
+
+#[derive(Resource)]
+struct VillagerAssets(Handle<Mesh>, Handle<StandardMaterial>);
+
+pub struct VillagerPlugin;
+
+impl Plugin for VillagerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Villager>()
+            .register_type::<VillagerParams>()
+            .insert_resource(VillagerParams {
+                chance: 0.6,
+                host_loss: 1.0,
+                nearby_loss: 0.4,
+                nearby_radius: 8.0,
+                flee_speed: 6.0,
+            })
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                move_in_villagers.run_if(in_state(GameState::BuildPhase)),
+            )
+            .add_systems(Update, (frighten_villagers, flee))
+            .add_systems(OnExit(GameState::ScoringPhase), cleanup);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(VillagerAssets(
+        meshes.add(Capsule3d::new(0.2, 0.4)),
+        materials.add(StandardMaterial {
+            base_color: Color::BLACK,
+            emissive: Color::srgb(1.6, 1.1, 0.4).into(),
+            ..default()
+        }),
+    ));
+}
+
+fn move_in_villagers(
+    mut commands: Commands,
+    query: Query<(Entity, &Aabb, &Anchors), Added<NeedsClearance>>,
+    villagers: Query<(), With<Villager>>,
+    params: Res<VillagerParams>,
+    assets: Res<VillagerAssets>,
+    mut rng: ResMut<RoundRng>,
+) {
+    let mut any = !villagers.is_empty();
+    for (entity, aabb, anchors) in &query {
+        let structural = anchors
+            .0
+            .iter()
+            .any(|(_, color, _, _)| matches!(color, AnchorColor::Up | AnchorColor::Down));
+        if !structural {
+            continue;
+        }
+        // Every castle gets at least one villager so the ruin phase has someone to drive out
+        if any && rng.0.f32() >= params.chance {
+            continue;
+        }
+        any = true;
+        let translation = Vec3::new(
+            aabb.center.x + (rng.0.f32() - 0.5) * aabb.half_extents.x,
+            aabb.center.y - aabb.half_extents.y * 0.5,
+            aabb.center.z + aabb.half_extents.z + 0.5,
+        );
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: assets.0.clone(),
+                    material: assets.1.clone(),
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+                Villager { morale: 1.0 },
+                Pickable::IGNORE,
+            ));
+        });
+    }
+}
+
+fn frighten_villagers(
+    mut commands: Commands,
+    ruined: Query<(Entity, &GlobalTransform), Or<(Added<Decayed>, Added<ReplacedBy>)>>,
+    mut villagers: Query<(Entity, &mut Villager, &GlobalTransform, Option<&Parent>), Without<Fleeing>>,
+    params: Res<VillagerParams>,
+) {
+    for (ruin_entity, ruin_transform) in &ruined {
+        for (entity, mut villager, transform, parent) in &mut villagers {
+            if villager.morale <= 0.0 {
+                continue;
+            }
+            if parent.map(|p| p.get() == ruin_entity).unwrap_or(false) {
+                villager.morale -= params.host_loss;
+            } else {
+                let d = (transform.translation() - ruin_transform.translation()).length();
+                villager.morale -= params.nearby_loss * (1.0 - d / params.nearby_radius).max(0.0);
+            }
+            if villager.morale <= 0.0 {
+                let direction = if transform.translation().x < 0.0 { -1.0 } else { 1.0 };
+                commands
+                    .entity(entity)
+                    .remove_parent_in_place()
+                    .insert(Fleeing(direction));
+            }
+        }
+    }
+}
+
+fn flee(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &Fleeing)>,
+    params: Res<VillagerParams>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, fleeing) in &mut query {
+        transform.translation.x += fleeing.0 * params.flee_speed * time.delta_seconds();
+        transform.translation.y -= params.flee_speed * 0.5 * time.delta_seconds();
+        transform.rotation = Quat::from_rotation_z(0.3 * (time.elapsed_seconds() * 12.0).sin());
+        if transform.translation.x.abs() > 40.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<Fleeing>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
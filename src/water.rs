@@ -0,0 +1,259 @@
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    utils::HashMap,
+};
+
+use crate::{
+    block::Block,
+    build_phase::OnTentacle,
+    crow::{Crow, Grab, Perched},
+    environmental_decoration::Water,
+    Spawner,
+};
+
+/// Impacts the water shader remembers, the oldest is overwritten first.
+const MAX_IMPACTS: usize = 8;
+/// Crows landing closer than this above the water disturb it.
+const TOUCH_HEIGHT: f32 = 1.5;
+const SPLASH_GRAVITY: f32 = 30.0;
+
+pub type WaterMaterial = ExtendedMaterial<StandardMaterial, WaterExtension>;
+
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+pub struct WaterExtension {
+    /// xy position, z time of impact, w strength
+    #[uniform(100)]
+    impacts: [Vec4; MAX_IMPACTS],
+}
+
+impl MaterialExtension for WaterExtension {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/water.wgsl".into()
+    }
+}
+
+/// Something hit the water at `position`, `strength` 1.0 for a block-sized splash.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct Splash {
+    pub position: Vec3,
+    pub strength: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct Impacts {
+    impacts: [Vec4; MAX_IMPACTS],
+    next: usize,
+}
+
+#[derive(Component)]
+struct SplashDrop(Vec3);
+
+#[derive(Resource)]
+struct SplashAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct WaterPlugin;
+
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<WaterMaterial>::default())
+            .add_event::<Splash>()
+            .init_resource::<Impacts>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    replace_water_material,
+                    detect_crossings,
+                    crows_touch_down,
+                    record_impacts,
+                    spawn_splash_drops,
+                    move_splash_drops,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(SplashAssets {
+        mesh: meshes.add(Sphere::new(0.08)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgba(0.0, 0.0, 0.0, 0.7),
+            emissive: Color::srgb(0.6, 0.65, 0.75).into(),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        }),
+    });
+}
+
+/// The height of the water's surface, if there is any water.
+fn surface(water: &Query<&GlobalTransform, With<Water>>) -> Option<f32> {
+    water.iter().map(|t| t.translation().y).reduce(f32::max)
+}
+
+pub fn replace_water_material(
+    mut commands: Commands,
+    water: Query<Entity, With<Water>>,
+    children: Query<&Children>,
+    meshes: Query<&Handle<StandardMaterial>>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut materials: ResMut<Assets<WaterMaterial>>,
+    impacts: Res<Impacts>,
+    mut replaced: Local<HashMap<AssetId<StandardMaterial>, Handle<WaterMaterial>>>,
+) {
+    for water in &water {
+        for entity in std::iter::once(water).chain(children.iter_descendants(water)) {
+            let Ok(original) = meshes.get(entity) else {
+                continue;
+            };
+            let handle = match replaced.get(&original.id()) {
+                Some(handle) => handle.clone(),
+                None => {
+                    let Some(base) = standard_materials.get(original).cloned() else {
+                        continue;
+                    };
+                    let handle = materials.add(WaterMaterial {
+                        base,
+                        extension: WaterExtension {
+                            impacts: impacts.impacts,
+                        },
+                    });
+                    replaced.insert(original.id(), handle.clone());
+                    handle
+                }
+            };
+            commands
+                .entity(entity)
+                .remove::<Handle<StandardMaterial>>()
+                .insert(handle);
+        }
+    }
+}
+
+/// Tentacle tips, loose blocks and crows splash whenever they pass through the surface.
+fn detect_crossings(
+    query: Query<
+        (Entity, &GlobalTransform, Has<Crow>),
+        Or<(
+            With<Spawner>,
+            (With<Block>, Without<OnTentacle>),
+            With<Crow>,
+        )>,
+    >,
+    water: Query<&GlobalTransform, With<Water>>,
+    mut splashes: EventWriter<Splash>,
+    mut below: Local<HashMap<Entity, bool>>,
+) {
+    let Some(surface) = surface(&water) else {
+        return;
+    };
+    let mut now_below = HashMap::new();
+    for (entity, transform, crow) in &query {
+        let translation = transform.translation();
+        let is_below = translation.y < surface;
+        if below.get(&entity).is_some_and(|was| *was != is_below) {
+            splashes.send(Splash {
+                position: translation.with_y(surface),
+                strength: if crow { 0.4 } else { 1.0 },
+            });
+        }
+        now_below.insert(entity, is_below);
+    }
+    *below = now_below;
+}
+
+fn crows_touch_down(
+    crows: Query<&GlobalTransform, (With<Crow>, Or<(Added<Perched>, Added<Grab>)>)>,
+    water: Query<&GlobalTransform, With<Water>>,
+    mut splashes: EventWriter<Splash>,
+) {
+    let Some(surface) = surface(&water) else {
+        return;
+    };
+    for transform in &crows {
+        let translation = transform.translation();
+        if (translation.y - surface).abs() < TOUCH_HEIGHT {
+            splashes.send(Splash {
+                position: translation.with_y(surface),
+                strength: 0.3,
+            });
+        }
+    }
+}
+
+fn record_impacts(
+    mut splashes: EventReader<Splash>,
+    mut impacts: ResMut<Impacts>,
+    mut materials: ResMut<Assets<WaterMaterial>>,
+    time: Res<Time>,
+) {
+    let mut changed = false;
+    for splash in splashes.read() {
+        let next = impacts.next;
+        impacts.impacts[next] = Vec4::new(
+            splash.position.x,
+            splash.position.y,
+            time.elapsed_seconds_wrapped(),
+            splash.strength,
+        );
+        impacts.next = (next + 1) % MAX_IMPACTS;
+        changed = true;
+    }
+    if changed {
+        for (_, material) in materials.iter_mut() {
+            material.extension.impacts = impacts.impacts;
+        }
+    }
+}
+
+fn spawn_splash_drops(
+    mut commands: Commands,
+    mut splashes: EventReader<Splash>,
+    assets: Res<SplashAssets>,
+) {
+    for splash in splashes.read() {
+        let count = (splash.strength * 12.0).ceil() as usize;
+        for _ in 0..count {
+            let velocity = Vec3::new(
+                (fastrand::f32() - 0.5) * 6.0,
+                (4.0 + fastrand::f32() * 6.0) * splash.strength.sqrt(),
+                (fastrand::f32() - 0.5) * 2.0,
+            );
+            commands.spawn((
+                PbrBundle {
+                    mesh: assets.mesh.clone(),
+                    material: assets.material.clone(),
+                    transform: Transform::from_translation(splash.position + Vec3::Y * 0.05),
+                    ..default()
+                },
+                SplashDrop(velocity),
+            ));
+        }
+    }
+}
+
+fn move_splash_drops(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut SplashDrop)>,
+    water: Query<&GlobalTransform, With<Water>>,
+    time: Res<Time>,
+) {
+    let surface = surface(&water).unwrap_or(-50.0);
+    let dt = time.delta_seconds();
+    for (entity, mut transform, mut drop) in &mut query {
+        drop.0.y -= SPLASH_GRAVITY * dt;
+        transform.translation += drop.0 * dt;
+        if transform.translation.y < surface {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+
+use crate::{reseed_round, GameState, RoundRng};
+
+/// The weather for the current round, rolled when building starts.
+#[derive(Resource, Reflect, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Fog,
+    Storm,
+}
+
+impl Weather {
+    /// Steady wind speed, world units per second.
+    pub fn wind(&self) -> f32 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 2.0,
+            Weather::Fog => 0.5,
+            Weather::Storm => 6.0,
+        }
+    }
+
+    /// Raindrops spawned per second.
+    pub fn rain(&self) -> f32 {
+        match self {
+            Weather::Clear | Weather::Fog => 0.0,
+            Weather::Rain => 120.0,
+            Weather::Storm => 300.0,
+        }
+    }
+
+    /// Opacity of the thickest fog layer.
+    pub fn fog(&self) -> f32 {
+        match self {
+            Weather::Clear => 0.0,
+            Weather::Rain => 0.15,
+            Weather::Fog => 0.6,
+            Weather::Storm => 0.25,
+        }
+    }
+
+    /// How cloudy the sky gets, relative to the counts authored for each cloud layer.
+    pub fn cloud_density(&self) -> f32 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 1.6,
+            Weather::Fog => 0.6,
+            Weather::Storm => 2.2,
+        }
+    }
+
+    /// How much further lightning can jump between conductors when everything is wet.
+    pub fn conduction(&self) -> f32 {
+        match self {
+            Weather::Clear | Weather::Fog => 1.0,
+            Weather::Rain => 1.4,
+            Weather::Storm => 1.6,
+        }
+    }
+}
+
+/// The current wind, the weather's steady wind plus gusts. Positive blows to the right.
+#[derive(Resource, Default)]
+pub struct Wind(pub f32);
+
+impl Wind {
+    /// Multiplier on debris damage, strong winds bring down what debris has loosened.
+    pub fn collapse(&self) -> f32 {
+        1.0 + self.0.abs() * 0.1
+    }
+
+    /// Chance that a block comes down along with the ruin it was standing on.
+    pub fn topple_chance(&self) -> f32 {
+        (self.0.abs() * 0.06).min(0.5)
+    }
+}
+
+/// How cloudy it is and which way the wind blows, rolled with the weather.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct CloudCover {
+    pub density: f32,
+    /// 1 for wind blowing to the right, -1 to the left.
+    pub wind_direction: f32,
+}
+
+#[derive(Component)]
+struct RainDrop(Vec3);
+
+#[derive(Component)]
+struct FogLayer {
+    opacity: f32,
+    height: f32,
+}
+
+#[derive(Resource)]
+struct WeatherAssets {
+    drop_mesh: Handle<Mesh>,
+    drop_material: Handle<StandardMaterial>,
+}
+
+const RAIN_SPEED: f32 = 40.0;
+const RAIN_TOP: f32 = 40.0;
+const RAIN_BOTTOM: f32 = -15.0;
+/// (height, depth, opacity relative to the thickest layer)
+const FOG_LAYERS: [(f32, f32, f32); 3] = [(2.0, -6.5, 1.0), (7.0, -3.0, 0.6), (0.0, 2.0, 0.35)];
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Weather>()
+            .register_type::<CloudCover>()
+            .init_resource::<Weather>()
+            .insert_resource(CloudCover {
+                density: 1.0,
+                wind_direction: 1.0,
+            })
+            .init_resource::<Wind>()
+            .add_systems(Startup, setup)
+            .add_systems(OnEnter(GameState::BuildPhase), roll_weather.after(reseed_round))
+            .add_systems(Update, (gust, spawn_rain, move_rain, fade_fog).chain());
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(WeatherAssets {
+        drop_mesh: meshes.add(Rectangle::new(0.05, 0.8)),
+        drop_material: materials.add(StandardMaterial {
+            base_color: Color::srgba(0.0, 0.0, 0.0, 0.5),
+            emissive: Color::srgb(0.5, 0.55, 0.65).into(),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        }),
+    });
+
+    let mesh = meshes.add(Rectangle::new(160.0, 10.0));
+    for (height, depth, opacity) in FOG_LAYERS {
+        commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::srgba(0.0, 0.0, 0.0, 0.0),
+                    emissive: Color::srgb(0.45, 0.45, 0.5).into(),
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                }),
+                transform: Transform::from_xyz(0.0, height, depth),
+                ..default()
+            },
+            FogLayer { opacity, height },
+        ));
+    }
+}
+
+fn roll_weather(
+    mut weather: ResMut<Weather>,
+    mut cover: ResMut<CloudCover>,
+    mut rng: ResMut<RoundRng>,
+) {
+    *weather = match rng.0.u32(0..10) {
+        0..=4 => Weather::Clear,
+        5..=6 => Weather::Rain,
+        7..=8 => Weather::Fog,
+        _ => Weather::Storm,
+    };
+    cover.density = weather.cloud_density();
+    cover.wind_direction = if rng.0.f32() < 0.7 { 1.0 } else { -1.0 };
+}
+
+fn gust(mut wind: ResMut<Wind>, weather: Res<Weather>, cover: Res<CloudCover>, time: Res<Time>) {
+    let t = time.elapsed_seconds();
+    let base = weather.wind() * cover.wind_direction;
+    let target = base * (1.0 + 0.4 * (t * 0.7).sin() * (t * 0.23).sin());
+    wind.0 += (target - wind.0) * (time.delta_seconds() * 0.5).min(1.0);
+}
+
+fn spawn_rain(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    wind: Res<Wind>,
+    assets: Res<WeatherAssets>,
+    time: Res<Time>,
+    mut owed: Local<f32>,
+) {
+    *owed += weather.rain() * time.delta_seconds();
+    let velocity = Vec3::new(wind.0, -RAIN_SPEED, 0.0);
+    while *owed >= 1.0 {
+        *owed -= 1.0;
+        let translation = Vec3::new(
+            -50.0 + fastrand::f32() * 100.0,
+            RAIN_TOP + fastrand::f32() * 5.0,
+            -6.0 + fastrand::f32() * 8.0,
+        );
+        commands.spawn((
+            PbrBundle {
+                mesh: assets.drop_mesh.clone(),
+                material: assets.drop_material.clone(),
+                transform: Transform::from_translation(translation)
+                    .with_rotation(Quat::from_rotation_z(velocity.x.atan2(-velocity.y))),
+                ..default()
+            },
+            RainDrop(velocity),
+        ));
+    }
+}
+
+fn move_rain(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &RainDrop)>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, drop) in &mut query {
+        transform.translation += drop.0 * time.delta_seconds();
+        if transform.translation.y < RAIN_BOTTOM {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn fade_fog(
+    mut query: Query<(&mut Transform, &FogLayer, &Handle<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    weather: Res<Weather>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+) {
+    let t = time.elapsed_seconds();
+    for (mut transform, layer, material) in &mut query {
+        // Layers roll with the wind and wrap around well off screen
+        transform.translation.x =
+            (transform.translation.x + wind.0 * layer.opacity * 0.2 * time.delta_seconds() + 40.0)
+                .rem_euclid(80.0)
+                - 40.0;
+        transform.translation.y = layer.height + (t * 0.3 + layer.height).sin() * 0.5;
+        let target = weather.fog() * layer.opacity;
+        let Some(alpha) = materials.get(material).map(|m| m.base_color.alpha()) else {
+            continue;
+        };
+        if (target - alpha).abs() > 0.001 {
+            if let Some(material) = materials.get_mut(material) {
+                material
+                    .base_color
+                    .set_alpha(alpha + (target - alpha) * (time.delta_seconds() * 0.5).min(1.0));
+            }
+        }
+    }
+}